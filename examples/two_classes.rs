@@ -0,0 +1,69 @@
+// Trains a small network to tell the points above the line y = x from the ones
+// below, then saves it and reloads it:
+// cargo run --example two_classes
+
+use std::error::Error;
+
+use neura_rust::prelude::*;
+use rand::Rng;
+
+fn points(samples: usize) -> InMemoryDataset {
+    let mut rng = rand::thread_rng();
+
+    let samples = (0..samples)
+        .map(|_| {
+            let (x, y) = (rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+            let above = (y > x) as u8 as f32;
+
+            (
+                DMatrix::from_vec(2, 1, vec![x, y]),
+                DMatrix::from_vec(2, 1, vec![1.0 - above, above]),
+            )
+        })
+        .collect();
+
+    InMemoryDataset::from_samples(samples)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Built from the registry so that the model can be saved
+    let activation = |name| activation_by_name(name).ok_or("Unknown activation");
+
+    let mut model = Model::with_loss(
+        vec![
+            Layer::with_activation(activation("relu")?, 2, 16),
+            Layer::with_activation(activation("softmax")?, 16, 2),
+        ],
+        loss_by_name("categorical_crossentropy").ok_or("Unknown loss")?,
+    );
+
+    model.fit(
+        FitOptions {
+            batch_size: 16,
+            epochs: 20,
+            learning_rate: 0.01,
+            metrics: vec![MetricKind::Accuracy.into()],
+            ..FitOptions::default()
+        },
+        &mut RMSProp::new(0.9),
+        &points(1000),
+    )?;
+
+    println!("\nTesting the network:\n");
+
+    model.test(vec![MetricKind::Accuracy.into()], &points(200))?;
+
+    let path = std::env::temp_dir().join("neura_rust_two_classes.json");
+    let path = path.to_str().ok_or("Non UTF-8 temp dir")?;
+
+    model.save(path)?;
+
+    let model = Model::<f32>::load(path)?;
+
+    println!(
+        "\nThe reloaded model puts (-0.5, 0.5) above the line with {:.2}",
+        model.evaluate(&DMatrix::from_vec(2, 1, vec![-0.5, 0.5]))?[1]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use std::{fmt, path::Path};
+
+use actix_web::{error::JsonPayloadError, http::StatusCode, HttpResponse, ResponseError};
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+
+use neura_rust::{error::NeuraError, preprocessing::encoders::LabelEncoder};
+
+pub const DEFAULT_TOP_K: usize = 3;
+
+fn default_top_k() -> usize {
+    DEFAULT_TOP_K
+}
+
+// What a prediction carries besides its top classes
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ResponseOptions {
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+    // Adds the probability of every class
+    #[serde(default)]
+    pub probabilities: bool,
+}
+
+impl Default for ResponseOptions {
+    fn default() -> Self {
+        Self {
+            top_k: DEFAULT_TOP_K,
+            probabilities: false,
+        }
+    }
+}
+
+impl ResponseOptions {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.top_k == 0 {
+            return Err(ApiError::bad_request("top_k must be positive"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MatrixData {
+    pub matrix: Vec<Vec<f32>>,
+    #[serde(flatten)]
+    pub options: ResponseOptions,
+}
+
+#[derive(Deserialize)]
+pub struct BatchData {
+    pub inputs: Vec<Vec<Vec<f32>>>,
+    #[serde(flatten)]
+    pub options: ResponseOptions,
+}
+
+// The rows of a matrix one after the other in an input column
+pub fn input_column(matrix: &[Vec<f32>]) -> DMatrix<f32> {
+    let values: Vec<f32> = matrix.iter().flatten().copied().collect();
+
+    DMatrix::from_vec(values.len(), 1, values)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelInfo {
+    pub name: String,
+    pub version: String,
+}
+
+impl ModelInfo {
+    // Named after the file, with a hash of its contents as the version so that
+    // a retrained model gets a new one
+    pub fn from_file(path: &str, contents: &[u8]) -> Self {
+        // FNV-1a, stable across builds unlike the std hasher
+        let hash = contents.iter().fold(0xcbf29ce484222325_u64, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+
+        Self {
+            name: Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string()),
+            version: format!("{:016x}", hash),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassScore {
+    pub class: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub probability: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Prediction {
+    // The most probable classes first
+    pub top: Vec<ClassScore>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probabilities: Option<Vec<f32>>,
+}
+
+impl Prediction {
+    pub fn new(
+        probabilities: &DMatrix<f32>,
+        labels: Option<&LabelEncoder>,
+        options: ResponseOptions,
+    ) -> Self {
+        let mut classes: Vec<usize> = (0..probabilities.len()).collect();
+
+        classes.sort_by(|&a, &b| probabilities[b].total_cmp(&probabilities[a]));
+        classes.truncate(options.top_k);
+
+        Self {
+            top: classes
+                .into_iter()
+                .map(|class| ClassScore {
+                    class,
+                    label: labels
+                        .and_then(|labels| labels.inverse_transform(class))
+                        .map(str::to_string),
+                    probability: probabilities[class],
+                })
+                .collect(),
+            probabilities: options
+                .probabilities
+                .then(|| probabilities.iter().copied().collect()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiResponse {
+    pub model: ModelInfo,
+    #[serde(flatten)]
+    pub prediction: Prediction,
+    pub latency_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub model: ModelInfo,
+    pub predictions: Vec<Prediction>,
+    pub latency_ms: f64,
+}
+
+// Sent as {"error": ..., "message": ...} with its status
+#[derive(Debug, Serialize)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: StatusCode,
+    pub error: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, error: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            error,
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "invalid_request", message)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.error, self.message)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status).json(self)
+    }
+}
+
+impl From<NeuraError> for ApiError {
+    fn from(error: NeuraError) -> Self {
+        match error {
+            NeuraError::ShapeMismatch { .. } => Self::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "shape_mismatch",
+                error.to_string(),
+            ),
+            _ => Self::new(StatusCode::BAD_REQUEST, "invalid_input", error.to_string()),
+        }
+    }
+}
+
+// Bodies that are not the expected JSON, for web::JsonConfig
+pub fn json_error(error: JsonPayloadError) -> ApiError {
+    let status = match &error {
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            StatusCode::PAYLOAD_TOO_LARGE
+        }
+        JsonPayloadError::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        _ => StatusCode::BAD_REQUEST,
+    };
+
+    ApiError::new(status, "invalid_json", error.to_string())
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use actix_web::{body::to_bytes, http::StatusCode, rt::System, ResponseError};
+    use nalgebra::DMatrix;
+
+    use neura_rust::{error::NeuraError, preprocessing::encoders::LabelEncoder};
+
+    use crate::api::{ApiError, ModelInfo, Prediction, ResponseOptions};
+
+    #[test]
+    fn test_prediction_top_classes_and_probabilities() {
+        let probabilities = DMatrix::from_vec(4, 1, vec![0.1, 0.6, 0.05, 0.25]);
+        let labels =
+            LabelEncoder::from_classes(["cat", "dog", "fish", "owl"].map(str::to_string).to_vec());
+
+        let prediction = Prediction::new(
+            &probabilities,
+            Some(&labels),
+            ResponseOptions {
+                top_k: 2,
+                probabilities: true,
+            },
+        );
+
+        assert_eq!(
+            vec![(1, Some("dog"), 0.6), (3, Some("owl"), 0.25)],
+            prediction
+                .top
+                .iter()
+                .map(|score| (score.class, score.label.as_deref(), score.probability))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(vec![0.1, 0.6, 0.05, 0.25]), prediction.probabilities);
+
+        // Without labels, and with fewer classes than asked for
+        let prediction = Prediction::new(
+            &DMatrix::from_vec(2, 1, vec![0.3, 0.7]),
+            None,
+            ResponseOptions::default(),
+        );
+
+        assert_eq!(2, prediction.top.len());
+        assert_eq!(None, prediction.top[0].label);
+        assert_eq!(None, prediction.probabilities);
+        assert_eq!(
+            r#"{"top":[{"class":1,"probability":0.7},{"class":0,"probability":0.3}]}"#,
+            serde_json::to_string(&prediction).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_model_info_from_file() {
+        let info = ModelInfo::from_file("./doodles/model.json", b"{}");
+
+        assert_eq!("model", info.name);
+        assert_eq!(16, info.version.len());
+        assert_ne!(
+            info.version,
+            ModelInfo::from_file("model.json", b"{ }").version
+        );
+    }
+
+    #[test]
+    fn test_errors_are_json_with_a_4xx_status() {
+        let error = ApiError::from(NeuraError::shape_mismatch("input 0", (2, 1), (3, 1)));
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, error.status_code());
+
+        let body = System::new()
+            .block_on(to_bytes(error.error_response().into_body()))
+            .unwrap();
+
+        assert_eq!(
+            r#"{"error":"shape_mismatch","message":"The input 0 should be 2x1 but is 3x1"}"#,
+            body
+        );
+
+        let error = ResponseOptions {
+            top_k: 0,
+            probabilities: false,
+        }
+        .validate()
+        .unwrap_err();
+
+        assert_eq!(StatusCode::BAD_REQUEST, error.status_code());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod tape;
+mod tape_test;
@@ -0,0 +1,605 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nalgebra::DMatrix;
+
+use crate::{
+    core::float::Float,
+    error::{check_shape, NeuraError},
+};
+
+// Reverse-mode automatic differentiation over DMatrix<T>.
+//
+// Every operation on a Var<T> records its result and its inputs on the Tape, the
+// gradients are then computed by walking the tape backwards from an output.
+// Operations on incompatible shapes record zeros and mark the tape as failed,
+// check and the gradients then return the first of those errors.
+#[derive(Clone)]
+pub struct Tape<T: Float = f32> {
+    nodes: Rc<RefCell<Vec<Node<T>>>>,
+    failure: Rc<RefCell<Option<ShapeFailure>>>,
+}
+
+#[derive(Clone, Copy)]
+struct ShapeFailure {
+    context: &'static str,
+    expected: (usize, usize),
+    found: (usize, usize),
+}
+
+#[derive(Clone)]
+pub struct Var<T: Float = f32> {
+    tape: Tape<T>,
+    index: usize,
+}
+
+pub struct Gradients<T: Float = f32> {
+    gradients: Vec<Option<DMatrix<T>>>,
+}
+
+struct Node<T: Float> {
+    value: DMatrix<T>,
+    operation: Operation<T>,
+}
+
+enum Operation<T: Float> {
+    Leaf,
+    MatMul(usize, usize),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Mul(usize, usize),
+    Div(usize, usize),
+    Neg(usize),
+    Scale(usize, T),
+    Exp(usize),
+    Ln(usize),
+    Powi(usize, i32),
+    Relu(usize),
+    Sigmoid(usize),
+    Tanh(usize),
+    Clamp(usize, T, T),
+    Transpose(usize),
+    Sum(usize),
+    Mean(usize),
+    SumRows(usize),
+    SumColumns(usize),
+    Map {
+        input: usize,
+        derivative: fn(&DMatrix<T>) -> DMatrix<T>,
+    },
+    Loss {
+        predicted: usize,
+        expected: DMatrix<T>,
+        derivative: fn(&DMatrix<T>, &DMatrix<T>) -> DMatrix<T>,
+    },
+}
+
+// A derived Default would require T: Default
+impl<T: Float> Default for Tape<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Rc::new(RefCell::new(Vec::new())),
+            failure: Rc::new(RefCell::new(None)),
+        }
+    }
+}
+
+impl<T: Float> Tape<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn variable(&self, value: DMatrix<T>) -> Var<T> {
+        self.push(value, Operation::Leaf)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.borrow().is_empty()
+    }
+
+    // Fails if an operation was recorded on incompatible shapes
+    pub fn check(&self) -> Result<(), NeuraError> {
+        match *self.failure.borrow() {
+            Some(failure) => Err(NeuraError::shape_mismatch(
+                failure.context,
+                failure.expected,
+                failure.found,
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // Gradients of a scalar (1x1) output with respect to every var of the tape.
+    // Other outputs need gradients_with_seed.
+    pub fn gradients(&self, output: &Var<T>) -> Result<Gradients<T>, NeuraError> {
+        self.check()?;
+
+        let shape = output.shape();
+
+        if shape != (1, 1) {
+            return Err(NeuraError::shape_mismatch("gradient output", (1, 1), shape));
+        }
+
+        self.gradients_with_seed(output, DMatrix::from_element(1, 1, T::one()))
+    }
+
+    // Vector-Jacobian product: propagates seed, the gradient of some scalar with
+    // respect to output, to every var recorded before output
+    pub fn gradients_with_seed(
+        &self,
+        output: &Var<T>,
+        seed: DMatrix<T>,
+    ) -> Result<Gradients<T>, NeuraError> {
+        self.check()?;
+
+        // The index of a var of another tape may point to any node of this one
+        if !Rc::ptr_eq(&self.nodes, &output.tape.nodes) {
+            return Err(NeuraError::InvalidArgument(
+                "The gradient output belongs to another tape".to_string(),
+            ));
+        }
+
+        let nodes = self.nodes.borrow();
+
+        if seed.shape() != nodes[output.index].value.shape() {
+            return Err(NeuraError::shape_mismatch(
+                "gradient seed",
+                nodes[output.index].value.shape(),
+                seed.shape(),
+            ));
+        }
+
+        let mut gradients: Vec<Option<DMatrix<T>>> = vec![None; output.index + 1];
+        gradients[output.index] = Some(seed);
+
+        for index in (0..=output.index).rev() {
+            let gradient = match gradients[index].take() {
+                Some(gradient) => gradient,
+                None => continue,
+            };
+
+            let node = &nodes[index];
+
+            let value = |input: usize| &nodes[input].value;
+
+            match &node.operation {
+                Operation::Leaf => {}
+                Operation::MatMul(a, b) => {
+                    accumulate(&mut gradients, *a, &gradient * value(*b).transpose());
+                    accumulate(&mut gradients, *b, value(*a).transpose() * &gradient);
+                }
+                Operation::Add(a, b) => {
+                    accumulate(&mut gradients, *a, reduce(&gradient, value(*a).shape()));
+                    accumulate(&mut gradients, *b, reduce(&gradient, value(*b).shape()));
+                }
+                Operation::Sub(a, b) => {
+                    accumulate(&mut gradients, *a, reduce(&gradient, value(*a).shape()));
+                    accumulate(&mut gradients, *b, -reduce(&gradient, value(*b).shape()));
+                }
+                Operation::Mul(a, b) => {
+                    let shape = gradient.shape();
+                    let (a_value, b_value) = (value(*a), value(*b));
+
+                    let a_gradient = gradient.component_mul(&broadcast(b_value, shape));
+                    let b_gradient = gradient.component_mul(&broadcast(a_value, shape));
+
+                    accumulate(&mut gradients, *a, reduce(&a_gradient, a_value.shape()));
+                    accumulate(&mut gradients, *b, reduce(&b_gradient, b_value.shape()));
+                }
+                Operation::Div(a, b) => {
+                    let shape = gradient.shape();
+                    let (a_value, b_value) = (value(*a), value(*b));
+                    let b_broadcast = broadcast(b_value, shape);
+
+                    let a_gradient = gradient.component_div(&b_broadcast);
+                    let b_gradient = -gradient
+                        .component_mul(&broadcast(a_value, shape))
+                        .component_div(&b_broadcast.map(|x| x * x));
+
+                    accumulate(&mut gradients, *a, reduce(&a_gradient, a_value.shape()));
+                    accumulate(&mut gradients, *b, reduce(&b_gradient, b_value.shape()));
+                }
+                Operation::Neg(a) => accumulate(&mut gradients, *a, -&gradient),
+                Operation::Scale(a, factor) => accumulate(&mut gradients, *a, &gradient * *factor),
+                Operation::Exp(a) => {
+                    accumulate(&mut gradients, *a, gradient.component_mul(&node.value))
+                }
+                Operation::Ln(a) => {
+                    accumulate(&mut gradients, *a, gradient.component_div(value(*a)))
+                }
+                Operation::Powi(a, exponent) => {
+                    let derivative =
+                        value(*a).map(|x| T::cast(*exponent as f64) * x.powi(exponent - 1));
+
+                    accumulate(&mut gradients, *a, gradient.component_mul(&derivative));
+                }
+                Operation::Relu(a) => {
+                    let derivative =
+                        value(*a).map(|x| if x > T::zero() { T::one() } else { T::zero() });
+
+                    accumulate(&mut gradients, *a, gradient.component_mul(&derivative));
+                }
+                Operation::Sigmoid(a) => {
+                    let derivative = node.value.map(|s| s * (T::one() - s));
+
+                    accumulate(&mut gradients, *a, gradient.component_mul(&derivative));
+                }
+                Operation::Tanh(a) => {
+                    let derivative = node.value.map(|t| T::one() - t * t);
+
+                    accumulate(&mut gradients, *a, gradient.component_mul(&derivative));
+                }
+                Operation::Clamp(a, min, max) => {
+                    let derivative = value(*a).map(|x| {
+                        if x > *min && x < *max {
+                            T::one()
+                        } else {
+                            T::zero()
+                        }
+                    });
+
+                    accumulate(&mut gradients, *a, gradient.component_mul(&derivative));
+                }
+                Operation::Transpose(a) => accumulate(&mut gradients, *a, gradient.transpose()),
+                Operation::Sum(a) => {
+                    accumulate(&mut gradients, *a, broadcast(&gradient, value(*a).shape()))
+                }
+                Operation::Mean(a) => {
+                    let a_value = value(*a);
+                    let mean_gradient = &gradient / T::cast(a_value.len() as f64);
+
+                    accumulate(
+                        &mut gradients,
+                        *a,
+                        broadcast(&mean_gradient, a_value.shape()),
+                    )
+                }
+                Operation::SumRows(a) | Operation::SumColumns(a) => {
+                    accumulate(&mut gradients, *a, broadcast(&gradient, value(*a).shape()))
+                }
+                Operation::Map { input, derivative } => {
+                    let derivative = derivative(value(*input));
+
+                    // Functions like softmax return a Jacobian instead of element-wise derivatives
+                    let input_gradient = if derivative.shape() == gradient.shape() {
+                        derivative.component_mul(&gradient)
+                    } else {
+                        derivative.transpose() * &gradient
+                    };
+
+                    accumulate(&mut gradients, *input, input_gradient);
+                }
+                Operation::Loss {
+                    predicted,
+                    expected,
+                    derivative,
+                } => {
+                    let input_gradient = derivative(expected, value(*predicted)) * gradient[0];
+
+                    accumulate(&mut gradients, *predicted, input_gradient);
+                }
+            }
+
+            gradients[index] = Some(gradient);
+        }
+
+        Ok(Gradients { gradients })
+    }
+
+    // Keeps the first failure, the later ones usually follow from it
+    fn fail(&self, context: &'static str, expected: (usize, usize), found: (usize, usize)) {
+        self.failure.borrow_mut().get_or_insert(ShapeFailure {
+            context,
+            expected,
+            found,
+        });
+    }
+
+    fn push(&self, value: DMatrix<T>, operation: Operation<T>) -> Var<T> {
+        let mut nodes = self.nodes.borrow_mut();
+
+        nodes.push(Node { value, operation });
+
+        Var {
+            tape: self.clone(),
+            index: nodes.len() - 1,
+        }
+    }
+}
+
+impl<T: Float> Var<T> {
+    pub fn value(&self) -> DMatrix<T> {
+        self.tape.nodes.borrow()[self.index].value.clone()
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        self.tape.nodes.borrow()[self.index].value.shape()
+    }
+
+    pub fn get_tape(&self) -> &Tape<T> {
+        &self.tape
+    }
+
+    // Creates a var on the same tape, e.g. for constants used along with self
+    pub fn constant(&self, value: DMatrix<T>) -> Var<T> {
+        self.tape.variable(value)
+    }
+
+    pub fn matmul(&self, other: &Var<T>) -> Var<T> {
+        let value = self.binary_value(other, |a, b| {
+            if a.ncols() == b.nrows() {
+                a * b
+            } else {
+                self.tape
+                    .fail("matmul operand", (a.ncols(), b.ncols()), b.shape());
+
+                DMatrix::zeros(a.nrows(), b.ncols())
+            }
+        });
+
+        self.tape
+            .push(value, Operation::MatMul(self.index, other.index))
+    }
+
+    // The element-wise operations broadcast scalars (1x1), column vectors (n x 1)
+    // and row vectors (1 x m) to the shape of the other operand
+    pub fn add(&self, other: &Var<T>) -> Var<T> {
+        let value = self.broadcast_value(other, |a, b| a + b);
+
+        self.tape
+            .push(value, Operation::Add(self.index, other.index))
+    }
+
+    pub fn sub(&self, other: &Var<T>) -> Var<T> {
+        let value = self.broadcast_value(other, |a, b| a - b);
+
+        self.tape
+            .push(value, Operation::Sub(self.index, other.index))
+    }
+
+    pub fn mul(&self, other: &Var<T>) -> Var<T> {
+        let value = self.broadcast_value(other, |a, b| a.component_mul(b));
+
+        self.tape
+            .push(value, Operation::Mul(self.index, other.index))
+    }
+
+    pub fn div(&self, other: &Var<T>) -> Var<T> {
+        let value = self.broadcast_value(other, |a, b| a.component_div(b));
+
+        self.tape
+            .push(value, Operation::Div(self.index, other.index))
+    }
+
+    pub fn add_scalar(&self, scalar: T) -> Var<T> {
+        self.add(&self.constant(DMatrix::from_element(1, 1, scalar)))
+    }
+
+    pub fn neg(&self) -> Var<T> {
+        self.unary(|a| -a, Operation::Neg(self.index))
+    }
+
+    pub fn scale(&self, factor: T) -> Var<T> {
+        self.unary(|a| a * factor, Operation::Scale(self.index, factor))
+    }
+
+    pub fn exp(&self) -> Var<T> {
+        self.unary(|a| a.map(|x| x.exp()), Operation::Exp(self.index))
+    }
+
+    pub fn ln(&self) -> Var<T> {
+        self.unary(|a| a.map(|x| x.ln()), Operation::Ln(self.index))
+    }
+
+    pub fn powi(&self, exponent: i32) -> Var<T> {
+        self.unary(
+            |a| a.map(|x| x.powi(exponent)),
+            Operation::Powi(self.index, exponent),
+        )
+    }
+
+    pub fn relu(&self) -> Var<T> {
+        self.unary(|a| a.map(|x| x.max(T::zero())), Operation::Relu(self.index))
+    }
+
+    pub fn sigmoid(&self) -> Var<T> {
+        self.unary(
+            |a| a.map(|x| T::one() / (T::one() + (-x).exp())),
+            Operation::Sigmoid(self.index),
+        )
+    }
+
+    pub fn tanh(&self) -> Var<T> {
+        self.unary(|a| a.map(|x| x.tanh()), Operation::Tanh(self.index))
+    }
+
+    pub fn clamp(&self, min: T, max: T) -> Var<T> {
+        self.unary(
+            |a| a.map(|x| x.clamp(min, max)),
+            Operation::Clamp(self.index, min, max),
+        )
+    }
+
+    pub fn transpose(&self) -> Var<T> {
+        self.unary(|a| a.transpose(), Operation::Transpose(self.index))
+    }
+
+    // Sum of all the elements, as a 1x1 matrix
+    pub fn sum(&self) -> Var<T> {
+        self.unary(
+            |a| DMatrix::from_element(1, 1, a.sum()),
+            Operation::Sum(self.index),
+        )
+    }
+
+    pub fn mean(&self) -> Var<T> {
+        self.unary(
+            |a| DMatrix::from_element(1, 1, a.mean()),
+            Operation::Mean(self.index),
+        )
+    }
+
+    // Sums every column over its rows, resulting in a 1 x m row vector
+    pub fn sum_rows(&self) -> Var<T> {
+        self.unary(sum_rows, Operation::SumRows(self.index))
+    }
+
+    // Sums every row over its columns, resulting in a n x 1 column vector
+    pub fn sum_columns(&self) -> Var<T> {
+        self.unary(sum_columns, Operation::SumColumns(self.index))
+    }
+
+    // Records a function whose derivative is already known, e.g. the activations
+    // with a hand written *_derivative
+    pub fn map(
+        &self,
+        function: fn(&DMatrix<T>) -> DMatrix<T>,
+        derivative: fn(&DMatrix<T>) -> DMatrix<T>,
+    ) -> Var<T> {
+        self.unary(
+            function,
+            Operation::Map {
+                input: self.index,
+                derivative,
+            },
+        )
+    }
+
+    // Records a loss whose derivative with respect to the prediction (self) is already known
+    pub fn loss(
+        &self,
+        expected: &DMatrix<T>,
+        loss: fn(&DMatrix<T>, &DMatrix<T>) -> T,
+        derivative: fn(&DMatrix<T>, &DMatrix<T>) -> DMatrix<T>,
+    ) -> Result<Var<T>, NeuraError> {
+        let value = {
+            let nodes = self.tape.nodes.borrow();
+
+            check_shape("prediction", expected.shape(), &nodes[self.index].value)?;
+
+            DMatrix::from_element(1, 1, loss(expected, &nodes[self.index].value))
+        };
+
+        Ok(self.tape.push(
+            value,
+            Operation::Loss {
+                predicted: self.index,
+                expected: expected.clone(),
+                derivative,
+            },
+        ))
+    }
+
+    fn unary(
+        &self,
+        function: impl Fn(&DMatrix<T>) -> DMatrix<T>,
+        operation: Operation<T>,
+    ) -> Var<T> {
+        let value = function(&self.tape.nodes.borrow()[self.index].value);
+
+        self.tape.push(value, operation)
+    }
+
+    fn binary_value(
+        &self,
+        other: &Var<T>,
+        function: impl Fn(&DMatrix<T>, &DMatrix<T>) -> DMatrix<T>,
+    ) -> DMatrix<T> {
+        let nodes = self.tape.nodes.borrow();
+
+        function(&nodes[self.index].value, &nodes[other.index].value)
+    }
+
+    fn broadcast_value(
+        &self,
+        other: &Var<T>,
+        function: impl Fn(&DMatrix<T>, &DMatrix<T>) -> DMatrix<T>,
+    ) -> DMatrix<T> {
+        self.binary_value(other, |a, b| match broadcast_shape(a.shape(), b.shape()) {
+            Ok(shape) => function(&broadcast(a, shape), &broadcast(b, shape)),
+            Err(_) => {
+                self.tape.fail("broadcast operand", a.shape(), b.shape());
+
+                DMatrix::zeros(a.nrows(), a.ncols())
+            }
+        })
+    }
+}
+
+impl<T: Float> Gradients<T> {
+    // Gradient with respect to var, zeros if the output does not depend on it
+    pub fn wrt(&self, var: &Var<T>) -> DMatrix<T> {
+        match self.gradients.get(var.index) {
+            Some(Some(gradient)) => gradient.clone(),
+            _ => {
+                let (rows, columns) = var.shape();
+
+                DMatrix::zeros(rows, columns)
+            }
+        }
+    }
+}
+
+fn accumulate<T: Float>(gradients: &mut [Option<DMatrix<T>>], index: usize, gradient: DMatrix<T>) {
+    match &mut gradients[index] {
+        Some(accumulated) => *accumulated += gradient,
+        empty => *empty = Some(gradient),
+    }
+}
+
+// Shape of the result of an element-wise operation on a and b
+pub(crate) fn broadcast_shape(
+    a: (usize, usize),
+    b: (usize, usize),
+) -> Result<(usize, usize), NeuraError> {
+    let dimension = |a: usize, b: usize| {
+        if a == b || b == 1 {
+            Some(a)
+        } else if a == 1 {
+            Some(b)
+        } else {
+            None
+        }
+    };
+
+    match (dimension(a.0, b.0), dimension(a.1, b.1)) {
+        (Some(rows), Some(columns)) => Ok((rows, columns)),
+        _ => Err(NeuraError::shape_mismatch("broadcast operand", a, b)),
+    }
+}
+
+fn broadcast<T: Float>(matrix: &DMatrix<T>, (rows, columns): (usize, usize)) -> DMatrix<T> {
+    if matrix.shape() == (rows, columns) {
+        return matrix.clone();
+    }
+
+    DMatrix::from_fn(rows, columns, |row, column| {
+        matrix[(row % matrix.nrows(), column % matrix.ncols())]
+    })
+}
+
+// Sums the gradient over the dimensions that were broadcast
+fn reduce<T: Float>(gradient: &DMatrix<T>, (rows, columns): (usize, usize)) -> DMatrix<T> {
+    let mut reduced = gradient.clone();
+
+    if rows == 1 && reduced.nrows() != 1 {
+        reduced = sum_rows(&reduced);
+    }
+
+    if columns == 1 && reduced.ncols() != 1 {
+        reduced = sum_columns(&reduced);
+    }
+
+    reduced
+}
+
+fn sum_rows<T: Float>(matrix: &DMatrix<T>) -> DMatrix<T> {
+    DMatrix::from_fn(1, matrix.ncols(), |_, column| matrix.column(column).sum())
+}
+
+fn sum_columns<T: Float>(matrix: &DMatrix<T>) -> DMatrix<T> {
+    DMatrix::from_fn(matrix.nrows(), 1, |row, _| matrix.row(row).sum())
+}
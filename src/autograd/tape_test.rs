@@ -0,0 +1,238 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        autograd::tape::{broadcast_shape, Tape, Var},
+        error::NeuraError,
+        functions::losses::{mse, mse_derivative},
+    };
+
+    #[test]
+    fn test_matmul_and_broadcast_add() {
+        let tape = Tape::new();
+
+        let weights = tape.variable(DMatrix::from_row_slice(
+            2,
+            3,
+            &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+        ));
+        let input = tape.variable(DMatrix::from_row_slice(
+            3,
+            2,
+            &[1.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+        ));
+        let biases = tape.variable(DMatrix::from_vec(2, 1, vec![0.5, -0.5]));
+
+        let output = weights.matmul(&input).add(&biases);
+
+        assert_eq!(
+            DMatrix::from_row_slice(2, 2, &[4.5, 5.5, 9.5, 10.5]),
+            output.value()
+        );
+
+        let gradients = tape.gradients(&output.sum()).unwrap();
+
+        assert_eq!(
+            DMatrix::from_row_slice(2, 3, &[1.0, 1.0, 2.0, 1.0, 1.0, 2.0]),
+            gradients.wrt(&weights)
+        );
+        assert_eq!(
+            DMatrix::from_row_slice(3, 2, &[5.0, 5.0, 7.0, 7.0, 9.0, 9.0]),
+            gradients.wrt(&input)
+        );
+        // The biases were broadcast over both columns
+        assert_eq!(
+            DMatrix::from_vec(2, 1, vec![2.0, 2.0]),
+            gradients.wrt(&biases)
+        );
+    }
+
+    #[test]
+    fn test_reductions() {
+        let tape = Tape::new();
+
+        let x = tape.variable(DMatrix::from_row_slice(2, 2, &[1.0, 2.0, 3.0, 4.0]));
+
+        assert_eq!(
+            DMatrix::from_row_slice(1, 2, &[4.0, 6.0]),
+            x.sum_rows().value()
+        );
+        assert_eq!(
+            DMatrix::from_vec(2, 1, vec![3.0, 7.0]),
+            x.sum_columns().value()
+        );
+        assert_eq!(2.5, x.mean().value()[0]);
+
+        let weights = tape.variable(DMatrix::from_row_slice(1, 2, &[1.0, 10.0]));
+        let output = x.sum_rows().mul(&weights).sum();
+
+        assert_eq!(64.0, output.value()[0]);
+        assert_eq!(
+            DMatrix::from_row_slice(2, 2, &[1.0, 10.0, 1.0, 10.0]),
+            tape.gradients(&output).unwrap().wrt(&x)
+        );
+    }
+
+    #[test]
+    fn test_gradients_with_seed() {
+        let tape = Tape::new();
+
+        let x = tape.variable(DMatrix::from_vec(2, 1, vec![1.0, 2.0]));
+        let squared = x.powi(2);
+
+        let gradients = tape
+            .gradients_with_seed(&squared, DMatrix::from_vec(2, 1, vec![1.0, 0.5]))
+            .unwrap();
+
+        assert_eq!(DMatrix::from_vec(2, 1, vec![2.0, 2.0]), gradients.wrt(&x));
+    }
+
+    #[test]
+    fn test_unused_var_has_zero_gradient() {
+        let tape = Tape::new();
+
+        let x = tape.variable(DMatrix::from_vec(2, 1, vec![1.0, 2.0]));
+        let unused = tape.variable(DMatrix::from_vec(3, 1, vec![1.0, 2.0, 3.0]));
+
+        let gradients = tape.gradients(&x.sum()).unwrap();
+
+        assert_eq!(DMatrix::zeros(3, 1), gradients.wrt(&unused));
+    }
+
+    #[test]
+    fn test_gradients_need_a_scalar_output_or_a_matching_seed() {
+        let tape = Tape::new();
+
+        let x = tape.variable(DMatrix::from_vec(2, 1, vec![1.0, 2.0]));
+        let squared = x.powi(2);
+
+        assert!(matches!(
+            tape.gradients(&squared),
+            Err(NeuraError::ShapeMismatch {
+                expected: (1, 1),
+                found: (2, 1),
+                ..
+            })
+        ));
+        assert!(matches!(
+            tape.gradients_with_seed(&squared, DMatrix::from_vec(3, 1, vec![1.0, 1.0, 1.0])),
+            Err(NeuraError::ShapeMismatch {
+                expected: (2, 1),
+                found: (3, 1),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_loss_needs_a_matching_prediction() {
+        let tape = Tape::new();
+
+        let predicted = tape.variable(DMatrix::from_vec(2, 1, vec![1.0, 2.0]));
+
+        assert!(matches!(
+            predicted.loss(&DMatrix::from_vec(3, 1, vec![1.0; 3]), mse, mse_derivative),
+            Err(NeuraError::ShapeMismatch {
+                expected: (3, 1),
+                found: (2, 1),
+                ..
+            })
+        ));
+
+        let loss = predicted
+            .loss(
+                &DMatrix::from_vec(2, 1, vec![1.0, 0.0]),
+                mse,
+                mse_derivative,
+            )
+            .unwrap();
+
+        assert!(tape.gradients(&loss).is_ok());
+    }
+
+    #[test]
+    fn test_gradients_of_a_var_of_another_tape_are_errors() {
+        let tape = Tape::new();
+        let other = Tape::new();
+
+        tape.variable(DMatrix::from_element(1, 1, 1.0));
+
+        let x = other.variable(DMatrix::from_element(1, 1, 2.0));
+        let output = x.powi(2).sum();
+
+        assert!(matches!(
+            tape.gradients(&output),
+            Err(NeuraError::InvalidArgument(_))
+        ));
+        assert!(other.gradients(&output).is_ok());
+    }
+
+    #[test]
+    fn test_incompatible_shapes_are_errors() {
+        assert_eq!((2, 3), broadcast_shape((2, 1), (1, 3)).unwrap());
+        assert!(broadcast_shape((2, 3), (3, 2)).is_err());
+
+        let tape = Tape::new();
+
+        let a = tape.variable(DMatrix::from_vec(2, 3, vec![1.0; 6]));
+        let b = tape.variable(DMatrix::from_vec(3, 2, vec![1.0; 6]));
+
+        assert!(tape.check().is_ok());
+
+        let output = a.add(&b).matmul(&a).sum();
+
+        assert!(matches!(
+            tape.check(),
+            Err(NeuraError::ShapeMismatch {
+                expected: (2, 3),
+                found: (3, 2),
+                ..
+            })
+        ));
+        assert!(tape.gradients(&output).is_err());
+
+        let tape = Tape::new();
+
+        let a = tape.variable(DMatrix::from_vec(2, 3, vec![1.0; 6]));
+
+        assert!(tape.gradients(&a.matmul(&a).sum()).is_err());
+    }
+
+    fn composite(x: &Var) -> Var {
+        let scaled = x.scale(0.5).tanh().mul(&x.sigmoid());
+        let shifted = x.exp().add_scalar(1.0).ln().div(&x.relu().add_scalar(2.0));
+
+        scaled.sub(&shifted.neg()).mul(&x.transpose().sum()).mean()
+    }
+
+    #[test]
+    fn test_elementwise_gradients_match_finite_differences() {
+        let values = DMatrix::from_vec(3, 1, vec![0.3, -1.2, 0.7]);
+
+        let tape = Tape::new();
+        let x = tape.variable(values.clone());
+        let analytic = tape.gradients(&composite(&x)).unwrap().wrt(&x);
+
+        let eps = 1e-2;
+
+        for i in 0..values.len() {
+            let mut plus = values.clone();
+            plus[i] += eps;
+            let mut minus = values.clone();
+            minus[i] -= eps;
+
+            let loss = |values: DMatrix<f32>| composite(&Tape::new().variable(values)).value()[0];
+
+            let numerical = (loss(plus) - loss(minus)) / (2.0 * eps);
+
+            assert!(
+                (analytic[i] - numerical).abs() < 1e-3,
+                "{}: analytic {} numerical {}",
+                i,
+                analytic[i],
+                numerical
+            );
+        }
+    }
+}
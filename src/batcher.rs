@@ -0,0 +1,90 @@
+use std::{
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use nalgebra::DMatrix;
+use tokio::sync::oneshot;
+
+use neura_rust::{core::model::Model, error::NeuraError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchOptions {
+    pub max_batch_size: usize,
+    // How long the first input of a batch waits for others
+    pub max_wait: Duration,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 32,
+            max_wait: Duration::from_millis(5),
+        }
+    }
+}
+
+type Reply = oneshot::Sender<Result<DMatrix<f32>, NeuraError>>;
+
+// Coalesces the inputs submitted concurrently into micro-batches that go
+// through a single batched forward pass on a worker thread
+pub struct Batcher {
+    sender: mpsc::Sender<(DMatrix<f32>, Reply)>,
+}
+
+impl Batcher {
+    pub fn new(model: Arc<Model>, options: BatchOptions) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || run(&model, options, receiver));
+
+        Self { sender }
+    }
+
+    // The prediction arrives once the batch of the input has been evaluated
+    pub fn submit(
+        &self,
+        input: DMatrix<f32>,
+    ) -> oneshot::Receiver<Result<DMatrix<f32>, NeuraError>> {
+        let (reply, receiver) = oneshot::channel();
+
+        // The worker only stops when the batcher is dropped
+        let _ = self.sender.send((input, reply));
+
+        receiver
+    }
+}
+
+fn run(model: &Model, options: BatchOptions, receiver: mpsc::Receiver<(DMatrix<f32>, Reply)>) {
+    while let Ok(first) = receiver.recv() {
+        let deadline = Instant::now() + options.max_wait;
+        let mut batch = vec![first];
+
+        while batch.len() < options.max_batch_size {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(job) => batch.push(job),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let (inputs, replies): (Vec<DMatrix<f32>>, Vec<Reply>) = batch.into_iter().unzip();
+
+        match model.evaluate_batch(&inputs) {
+            Ok(predictions) => {
+                for (reply, prediction) in replies.into_iter().zip(predictions) {
+                    let _ = reply.send(Ok(prediction));
+                }
+            }
+            // A malformed input fails its own request only
+            Err(_) => {
+                for (reply, input) in replies.into_iter().zip(inputs.iter()) {
+                    let _ = reply.send(model.evaluate(input));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use nalgebra::DMatrix;
+
+    use neura_rust::{
+        core::{layer::Layer, model::Model},
+        error::NeuraError,
+        functions::{
+            activations::{sigmoid, sigmoid_derivative, softmax, softmax_derivative},
+            losses::{categorical_crossentropy, categorical_crossentropy_derivative},
+        },
+    };
+
+    use crate::batcher::{BatchOptions, Batcher};
+
+    #[test]
+    fn test_batches_concurrent_inputs() {
+        let model = Arc::new(Model::new(
+            vec![
+                Layer::new(sigmoid, sigmoid_derivative, 2, 4),
+                Layer::new(softmax, softmax_derivative, 4, 3),
+            ],
+            categorical_crossentropy,
+            categorical_crossentropy_derivative,
+        ));
+
+        let batcher = Batcher::new(
+            Arc::clone(&model),
+            BatchOptions {
+                max_batch_size: 4,
+                max_wait: Duration::from_millis(20),
+            },
+        );
+
+        let mut inputs: Vec<DMatrix<f32>> = (0..6)
+            .map(|index| DMatrix::from_vec(2, 1, vec![index as f32, 1.0]))
+            .collect();
+        inputs.insert(2, DMatrix::zeros(5, 1));
+
+        let receivers: Vec<_> = inputs
+            .iter()
+            .map(|input| batcher.submit(input.clone()))
+            .collect();
+
+        for (input, receiver) in inputs.iter().zip(receivers) {
+            let prediction = receiver.blocking_recv().unwrap();
+
+            match model.evaluate(input) {
+                Ok(expected) => assert!((expected - prediction.unwrap()).abs().max() < 1e-6),
+                Err(_) => assert!(matches!(
+                    prediction,
+                    Err(NeuraError::ShapeMismatch { found: (5, 1), .. })
+                )),
+            }
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use std::{collections::HashMap, error::Error, fmt, time::Duration};
+
+use neura_rust::config::{DataFormat, TrainingConfig, DEFAULT_MODEL_PATH};
+
+use crate::batcher::BatchOptions;
+
+pub const USAGE: &str = "Usage: neura_rust <command> [options]
+
+Commands:
+  train     --config <file.toml|json|yaml> --dataset <csv> --test-dataset <csv> --format <doodles|mnist>
+            --layers <units,...> --activation <name> --epochs <n> --batch-size <n>
+            --optimizer <rmsprop> --learning-rate <rate> --output <model.json>
+            --augment --no-augment --class-weights --no-class-weights
+  evaluate  --model <model.json> --dataset <csv> --format <doodles|mnist>
+            --confusion-matrix <csv>
+  predict   --model <model.json> --input <csv|npy> --output <csv>
+  serve     --model <model.json> --address <host:port>
+            --max-batch-size <n> --max-wait-ms <ms>
+
+Serving is the default command, and its model can also be given with NEURA_MODEL.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluateOptions {
+    pub model: String,
+    pub dataset: String,
+    pub format: DataFormat,
+    pub confusion_matrix: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictOptions {
+    pub model: String,
+    pub input: String,
+    // Prints the predictions when None
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServeOptions {
+    pub model: String,
+    pub address: String,
+    // How the concurrent /upload requests are batched
+    pub batch: BatchOptions,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Train(TrainingConfig),
+    Evaluate(EvaluateOptions),
+    Predict(PredictOptions),
+    Serve(ServeOptions),
+    Help,
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    UnknownCommand(String),
+    UnknownFlag { command: String, flag: String },
+    MissingValue(String),
+    MissingFlag { command: String, flag: String },
+    InvalidValue { flag: String, value: String },
+    Config { path: String, message: String },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownCommand(command) => write!(f, "Unknown command {}", command),
+            CliError::UnknownFlag { command, flag } => {
+                write!(f, "Unknown option {} for {}", flag, command)
+            }
+            CliError::MissingValue(flag) => write!(f, "Option {} needs a value", flag),
+            CliError::MissingFlag { command, flag } => write!(f, "{} needs {}", command, flag),
+            CliError::InvalidValue { flag, value } => {
+                write!(f, "Invalid value {} for {}", value, flag)
+            }
+            // Without a config file the flags made the defaults invalid
+            CliError::Config { path, message } if path.is_empty() => {
+                write!(f, "Invalid options: {}", message)
+            }
+            CliError::Config { path, message } => {
+                write!(f, "Invalid config file {}: {}", path, message)
+            }
+        }
+    }
+}
+
+impl Error for CliError {}
+
+// Options without a value
+const SWITCHES: [&str; 4] = [
+    "--augment",
+    "--no-augment",
+    "--class-weights",
+    "--no-class-weights",
+];
+
+// Values of the options of a command, the switches map to an empty value
+struct Flags {
+    command: String,
+    values: HashMap<String, String>,
+}
+
+impl Flags {
+    fn parse(command: &str, args: &[String], known: &[&str]) -> Result<Self, CliError> {
+        let mut values = HashMap::new();
+        let mut args = args.iter();
+
+        while let Some(flag) = args.next() {
+            if !known.contains(&flag.as_str()) {
+                return Err(CliError::UnknownFlag {
+                    command: command.to_string(),
+                    flag: flag.clone(),
+                });
+            }
+
+            let value = if SWITCHES.contains(&flag.as_str()) {
+                String::new()
+            } else {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| CliError::MissingValue(flag.clone()))?
+            };
+
+            values.insert(flag.clone(), value);
+        }
+
+        Ok(Self {
+            command: command.to_string(),
+            values,
+        })
+    }
+
+    fn get(&self, flag: &str) -> Option<String> {
+        self.values.get(flag).cloned()
+    }
+
+    fn has(&self, flag: &str) -> bool {
+        self.values.contains_key(flag)
+    }
+
+    fn required(&self, flag: &str) -> Result<String, CliError> {
+        self.get(flag).ok_or_else(|| CliError::MissingFlag {
+            command: self.command.clone(),
+            flag: flag.to_string(),
+        })
+    }
+
+    fn parsed<V: std::str::FromStr>(&self, flag: &str) -> Result<Option<V>, CliError> {
+        self.get(flag)
+            .map(|value| {
+                value.parse().map_err(|_| CliError::InvalidValue {
+                    flag: flag.to_string(),
+                    value,
+                })
+            })
+            .transpose()
+    }
+
+    fn format(&self) -> Result<Option<DataFormat>, CliError> {
+        self.get("--format")
+            .map(|value| match value.as_str() {
+                "doodles" => Ok(DataFormat::Doodles),
+                "mnist" => Ok(DataFormat::Mnist),
+                _ => Err(CliError::InvalidValue {
+                    flag: "--format".to_string(),
+                    value,
+                }),
+            })
+            .transpose()
+    }
+
+    fn model(&self) -> String {
+        self.get("--model")
+            .or_else(|| std::env::var("NEURA_MODEL").ok())
+            .unwrap_or_else(|| DEFAULT_MODEL_PATH.to_string())
+    }
+}
+
+// Arguments after the program name
+pub fn parse_args(args: &[String]) -> Result<Command, CliError> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) if !command.starts_with("--") => (command.as_str(), args),
+        // Flags only, as in the server before the subcommands
+        _ => ("serve", args),
+    };
+
+    match command {
+        "train" => parse_train(args).map(Command::Train),
+        "evaluate" => {
+            let flags = Flags::parse(
+                command,
+                args,
+                &["--model", "--dataset", "--format", "--confusion-matrix"],
+            )?;
+
+            Ok(Command::Evaluate(EvaluateOptions {
+                model: flags.model(),
+                dataset: flags.required("--dataset")?,
+                format: flags.format()?.unwrap_or(DataFormat::Doodles),
+                confusion_matrix: flags.get("--confusion-matrix"),
+            }))
+        }
+        "predict" => {
+            let flags = Flags::parse(command, args, &["--model", "--input", "--output"])?;
+
+            Ok(Command::Predict(PredictOptions {
+                model: flags.model(),
+                input: flags.required("--input")?,
+                output: flags.get("--output"),
+            }))
+        }
+        "serve" => {
+            let flags = Flags::parse(
+                command,
+                args,
+                &["--model", "--address", "--max-batch-size", "--max-wait-ms"],
+            )?;
+
+            let mut batch = BatchOptions::default();
+
+            if let Some(max_batch_size) = flags.parsed("--max-batch-size")? {
+                if max_batch_size == 0 {
+                    return Err(CliError::InvalidValue {
+                        flag: "--max-batch-size".to_string(),
+                        value: "0".to_string(),
+                    });
+                }
+
+                batch.max_batch_size = max_batch_size;
+            }
+            if let Some(max_wait_ms) = flags.parsed("--max-wait-ms")? {
+                batch.max_wait = Duration::from_millis(max_wait_ms);
+            }
+
+            Ok(Command::Serve(ServeOptions {
+                model: flags.model(),
+                address: flags
+                    .get("--address")
+                    .unwrap_or_else(|| "127.0.0.1:8000".to_string()),
+                batch,
+            }))
+        }
+        "help" | "-h" => Ok(Command::Help),
+        _ => Err(CliError::UnknownCommand(command.to_string())),
+    }
+}
+
+// The config file, if any, with the flags overriding its fields
+fn parse_train(args: &[String]) -> Result<TrainingConfig, CliError> {
+    let flags = Flags::parse(
+        "train",
+        args,
+        &[
+            "--config",
+            "--dataset",
+            "--test-dataset",
+            "--format",
+            "--layers",
+            "--activation",
+            "--epochs",
+            "--batch-size",
+            "--optimizer",
+            "--learning-rate",
+            "--output",
+            "--augment",
+            "--no-augment",
+            "--class-weights",
+            "--no-class-weights",
+        ],
+    )?;
+
+    let config_error = |path: &str, message: String| CliError::Config {
+        path: path.to_string(),
+        message,
+    };
+
+    let mut config = match flags.get("--config") {
+        Some(path) => {
+            TrainingConfig::load(&path).map_err(|error| config_error(&path, error.to_string()))?
+        }
+        None => TrainingConfig::default(),
+    };
+
+    if let Some(dataset) = flags.get("--dataset") {
+        config.data.train = dataset;
+    }
+    if let Some(test_dataset) = flags.get("--test-dataset") {
+        config.data.test = Some(test_dataset);
+    }
+    if let Some(format) = flags.format()? {
+        config.data.format = format;
+    }
+    if flags.has("--layers") || flags.has("--activation") {
+        let units = flags
+            .get("--layers")
+            .map(|layers| {
+                layers
+                    .split(',')
+                    .filter(|units| !units.is_empty())
+                    .map(|units| units.trim().parse())
+                    .collect::<Result<Vec<usize>, _>>()
+                    .map_err(|_| CliError::InvalidValue {
+                        flag: "--layers".to_string(),
+                        value: layers.clone(),
+                    })
+            })
+            .transpose()?;
+
+        config
+            .model
+            .set_hidden_layers(units, flags.get("--activation"));
+    }
+    if let Some(epochs) = flags.parsed("--epochs")? {
+        config.fit.epochs = epochs;
+    }
+    if let Some(batch_size) = flags.parsed("--batch-size")? {
+        config.fit.batch_size = batch_size;
+    }
+    if let Some(optimizer) = flags.get("--optimizer") {
+        config.optimizer.name = optimizer;
+    }
+    if let Some(learning_rate) = flags.parsed("--learning-rate")? {
+        config.fit.learning_rate = learning_rate;
+    }
+    if let Some(output) = flags.get("--output") {
+        config.output = output;
+    }
+    if flags.has("--augment") {
+        config.data.augment = true;
+    }
+    if flags.has("--no-augment") {
+        config.data.augment = false;
+    }
+    if flags.has("--class-weights") {
+        config.data.class_weights = true;
+    }
+    if flags.has("--no-class-weights") {
+        config.data.class_weights = false;
+    }
+
+    // The flags can make the config invalid as well
+    config.validate().map_err(|error| {
+        config_error(
+            &flags.get("--config").unwrap_or_default(),
+            error.to_string(),
+        )
+    })?;
+
+    Ok(config)
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use neura_rust::config::{DataFormat, LayerConfig, TrainingConfig};
+
+    use crate::{
+        batcher::BatchOptions,
+        cli::{parse_args, CliError, Command, PredictOptions},
+    };
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_train_flags_override_the_config() {
+        let path = std::env::temp_dir().join("neura_rust_cli_config.json");
+
+        fs::write(
+            &path,
+            r#"{"fit": {"epochs": 3}, "data": {"format": "mnist", "augment": true}}"#,
+        )
+        .unwrap();
+
+        let command = parse_args(&args(&format!(
+            "train --config {} --epochs 5 --layers 32,16 --no-augment --output model.json",
+            path.display()
+        )))
+        .unwrap();
+
+        let mut expected = TrainingConfig::default();
+
+        expected.fit.epochs = 5;
+        expected.data.format = DataFormat::Mnist;
+        expected.data.augment = false;
+        expected.model.layers = vec![
+            LayerConfig::new(Some(32), "relu"),
+            LayerConfig::new(Some(16), "relu"),
+            LayerConfig::new(None, "softmax"),
+        ];
+        expected.output = "model.json".to_string();
+
+        assert_eq!(Command::Train(expected), command);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_commands_and_errors() {
+        assert_eq!(
+            Command::Predict(PredictOptions {
+                model: "model.json".to_string(),
+                input: "inputs.csv".to_string(),
+                output: None,
+            }),
+            parse_args(&args("predict --model model.json --input inputs.csv")).unwrap()
+        );
+
+        assert!(matches!(
+            parse_args(&args("--model model.json")),
+            Ok(Command::Serve(options)) if options.model == "model.json"
+        ));
+        assert!(matches!(
+            parse_args(&args("train --augment")),
+            Ok(Command::Train(config)) if config.data.augment
+        ));
+        assert!(matches!(
+            parse_args(&args("train")),
+            Ok(Command::Train(config)) if !config.data.augment && !config.data.class_weights
+        ));
+        assert!(matches!(
+            parse_args(&args("train --class-weights")),
+            Ok(Command::Train(config)) if config.data.class_weights
+        ));
+        assert!(matches!(
+            parse_args(&args("fit")),
+            Err(CliError::UnknownCommand(command)) if command == "fit"
+        ));
+        assert!(matches!(
+            parse_args(&args("train --epochs ten")),
+            Err(CliError::InvalidValue { flag, .. }) if flag == "--epochs"
+        ));
+        assert!(matches!(
+            parse_args(&args("train --batch-size 0")),
+            Err(CliError::Config { message, .. }) if message == "fit.batch_size: must be positive"
+        ));
+        assert!(matches!(
+            parse_args(&args("evaluate --model model.json")),
+            Err(CliError::MissingFlag { flag, .. }) if flag == "--dataset"
+        ));
+        assert!(matches!(
+            parse_args(&args("serve --max-batch-size 8 --max-wait-ms 2")),
+            Ok(Command::Serve(options)) if options.batch == BatchOptions {
+                max_batch_size: 8,
+                max_wait: Duration::from_millis(2),
+            }
+        ));
+        assert!(matches!(
+            parse_args(&args("serve --max-batch-size 0")),
+            Err(CliError::InvalidValue { flag, .. }) if flag == "--max-batch-size"
+        ));
+        assert!(matches!(
+            parse_args(&args("serve --port 80")),
+            Err(CliError::UnknownFlag { flag, .. }) if flag == "--port"
+        ));
+    }
+}
@@ -0,0 +1,576 @@
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::{
+    core::{float::Float, layer::Layer, model::Model},
+    functions::{
+        metric::MetricKind,
+        registry::{activation_by_name, loss_by_name, metric_by_name},
+    },
+    optimizers::schedule::LearningRateSchedule,
+};
+
+// Where the trained model is saved, and the model served by default
+pub const DEFAULT_MODEL_PATH: &str = "./doodles/model.json";
+
+// Top-3 accuracy is the one the product tracks
+pub const DEFAULT_METRICS: [&str; 5] = [
+    "accuracy",
+    "top-3-accuracy",
+    "recall",
+    "f1-score",
+    "precision",
+];
+
+// Layout of the CSV datasets
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    Doodles,
+    Mnist,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DataConfig {
+    pub train: String,
+    pub test: Option<String>,
+    pub format: DataFormat,
+    // Rotations, scaling and shifts of the 28x28 images, off unless enabled
+    pub augment: bool,
+    // Balanced weights of the classes in the loss, off unless enabled
+    pub class_weights: bool,
+}
+
+impl Default for DataConfig {
+    fn default() -> Self {
+        Self {
+            train: "./doodles/train-quick-draw.csv".to_string(),
+            test: Some("./doodles/test-quick-draw.csv".to_string()),
+            format: DataFormat::Doodles,
+            augment: false,
+            class_weights: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayerConfig {
+    // The output layer has one unit per target when left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<usize>,
+    pub activation: String,
+}
+
+impl LayerConfig {
+    pub fn new(units: Option<usize>, activation: &str) -> Self {
+        Self {
+            units,
+            activation: activation.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelConfig {
+    pub layers: Vec<LayerConfig>,
+    pub loss: String,
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        Self {
+            layers: vec![
+                LayerConfig::new(Some(1024), "relu"),
+                LayerConfig::new(Some(512), "relu"),
+                LayerConfig::new(None, "softmax"),
+            ],
+            loss: "categorical_crossentropy".to_string(),
+        }
+    }
+}
+
+impl ModelConfig {
+    // Replaces the units or the activation of the layers before the output one
+    pub fn set_hidden_layers(&mut self, units: Option<Vec<usize>>, activation: Option<String>) {
+        let output = self
+            .layers
+            .pop()
+            .unwrap_or_else(|| LayerConfig::new(None, "softmax"));
+
+        let activation = activation
+            .or_else(|| self.layers.first().map(|layer| layer.activation.clone()))
+            .unwrap_or_else(|| "relu".to_string());
+        let units =
+            units.unwrap_or_else(|| self.layers.iter().filter_map(|layer| layer.units).collect());
+
+        self.layers = units
+            .into_iter()
+            .map(|units| LayerConfig::new(Some(units), &activation))
+            .chain([output])
+            .collect();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OptimizerConfig {
+    pub name: String,
+    pub decay_rate: f32,
+}
+
+impl Default for OptimizerConfig {
+    fn default() -> Self {
+        Self {
+            name: "rmsprop".to_string(),
+            decay_rate: 0.9,
+        }
+    }
+}
+
+// Options of Model::fit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FitConfig {
+    pub epochs: usize,
+    pub batch_size: usize,
+    // Learning rate of the first epoch, the schedule gives the next ones
+    pub learning_rate: f32,
+    pub schedule: LearningRateSchedule,
+}
+
+impl Default for FitConfig {
+    fn default() -> Self {
+        Self {
+            epochs: 10,
+            batch_size: 64,
+            learning_rate: 0.001,
+            schedule: LearningRateSchedule::Constant,
+        }
+    }
+}
+
+// Everything a training run reads, the missing fields keep the defaults
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrainingConfig {
+    pub data: DataConfig,
+    pub model: ModelConfig,
+    pub optimizer: OptimizerConfig,
+    pub fit: FitConfig,
+    pub metrics: Vec<String>,
+    // Where the model is saved, the effective config and the confusion matrix go
+    // next to it
+    pub output: String,
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        Self {
+            data: DataConfig::default(),
+            model: ModelConfig::default(),
+            optimizer: OptimizerConfig::default(),
+            fit: FitConfig::default(),
+            metrics: DEFAULT_METRICS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            output: DEFAULT_MODEL_PATH.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    // Like model.layers[1].units, empty for the whole config
+    pub path: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(path: &str, message: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    // The extension isn't .json, .toml, .yaml or .yml
+    UnsupportedFormat(String),
+    Syntax(String),
+    // A field of the wrong type or unknown
+    Field(FieldError),
+    Invalid(Vec<FieldError>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "{}", error),
+            ConfigError::UnsupportedFormat(path) => {
+                write!(f, "Unsupported config format of {}", path)
+            }
+            ConfigError::Syntax(message) => write!(f, "Syntax error: {}", message),
+            ConfigError::Field(error) => write!(f, "{}", error),
+            ConfigError::Invalid(errors) => write!(
+                f,
+                "{}",
+                errors
+                    .iter()
+                    .map(FieldError::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Ok(ConfigFormat::Json),
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("yaml" | "yml") => Ok(ConfigFormat::Yaml),
+            _ => Err(ConfigError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+}
+
+fn syntax_error<E: fmt::Display>(error: E) -> ConfigError {
+    ConfigError::Syntax(error.to_string())
+}
+
+// YAML documents read as JSON, so the three formats share the deserialization
+fn yaml_to_json(yaml: Yaml) -> Result<Value, ConfigError> {
+    Ok(match yaml {
+        Yaml::Null => Value::Null,
+        Yaml::Boolean(value) => Value::Bool(value),
+        Yaml::Integer(value) => Value::from(value),
+        Yaml::Real(value) => value
+            .parse()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| ConfigError::Syntax(format!("Invalid number {}", value)))?,
+        Yaml::String(value) => Value::String(value),
+        Yaml::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(yaml_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Yaml::Hash(entries) => {
+            let mut map = Map::new();
+
+            for (key, value) in entries {
+                let key = match key {
+                    Yaml::String(key) | Yaml::Real(key) => key,
+                    Yaml::Integer(key) => key.to_string(),
+                    Yaml::Boolean(key) => key.to_string(),
+                    key => return Err(ConfigError::Syntax(format!("Invalid key {:?}", key))),
+                };
+
+                map.insert(key, yaml_to_json(value)?);
+            }
+
+            Value::Object(map)
+        }
+        yaml => return Err(ConfigError::Syntax(format!("Unsupported value {:?}", yaml))),
+    })
+}
+
+fn json_to_yaml(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(value) => Yaml::Boolean(*value),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => Yaml::Integer(value),
+            None => Yaml::Real(number.to_string()),
+        },
+        Value::String(value) => Yaml::String(value.clone()),
+        Value::Array(values) => Yaml::Array(values.iter().map(json_to_yaml).collect()),
+        Value::Object(map) => Yaml::Hash(
+            map.iter()
+                .map(|(key, value)| (Yaml::String(key.clone()), json_to_yaml(value)))
+                .collect(),
+        ),
+    }
+}
+
+impl TrainingConfig {
+    // Parses and validates the config
+    pub fn parse(text: &str, format: ConfigFormat) -> Result<Self, ConfigError> {
+        let value = match format {
+            ConfigFormat::Json => serde_json::from_str(text).map_err(syntax_error)?,
+            ConfigFormat::Toml => {
+                serde_json::to_value(toml::from_str::<toml::Value>(text).map_err(syntax_error)?)
+                    .map_err(syntax_error)?
+            }
+            ConfigFormat::Yaml => {
+                match YamlLoader::load_from_str(text)
+                    .map_err(syntax_error)?
+                    .into_iter()
+                    .next()
+                {
+                    Some(yaml) => yaml_to_json(yaml)?,
+                    None => Value::Object(Map::new()),
+                }
+            }
+        };
+
+        // An empty document keeps every default
+        let value = match value {
+            Value::Null => Value::Object(Map::new()),
+            value => value,
+        };
+
+        let config: Self = serde_path_to_error::deserialize(value).map_err(|error| {
+            let path = error.path().to_string();
+
+            ConfigError::Field(FieldError {
+                path: if path == "." { String::new() } else { path },
+                message: error.into_inner().to_string(),
+            })
+        })?;
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    // The format is given by the extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let format = ConfigFormat::from_path(&path)?;
+
+        Self::parse(&fs::read_to_string(path)?, format)
+    }
+
+    pub fn to_text(&self, format: ConfigFormat) -> Result<String, ConfigError> {
+        match format {
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(syntax_error),
+            ConfigFormat::Toml => toml::to_string_pretty(self).map_err(syntax_error),
+            ConfigFormat::Yaml => {
+                let mut text = String::new();
+
+                YamlEmitter::new(&mut text)
+                    .dump(&json_to_yaml(
+                        &serde_json::to_value(self).map_err(syntax_error)?,
+                    ))
+                    .map_err(syntax_error)?;
+
+                Ok(text + "\n")
+            }
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let text = self.to_text(ConfigFormat::from_path(&path)?)?;
+
+        Ok(fs::write(path, text)?)
+    }
+
+    // Collects the errors of all the fields
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+
+        if self.data.train.is_empty() {
+            errors.push(FieldError::new("data.train", "missing the train dataset"));
+        }
+
+        if self.model.layers.is_empty() {
+            errors.push(FieldError::new("model.layers", "needs at least one layer"));
+        }
+
+        for (i, layer) in self.model.layers.iter().enumerate() {
+            let path = format!("model.layers[{}]", i);
+
+            match layer.units {
+                Some(0) => errors.push(FieldError::new(
+                    &format!("{}.units", path),
+                    "must be positive",
+                )),
+                None if i + 1 < self.model.layers.len() => errors.push(FieldError::new(
+                    &format!("{}.units", path),
+                    "only the output layer can leave out its units",
+                )),
+                _ => {}
+            }
+
+            if activation_by_name::<f32>(&layer.activation).is_none() {
+                errors.push(FieldError::new(
+                    &format!("{}.activation", path),
+                    &format!("unknown activation {}", layer.activation),
+                ));
+            }
+        }
+
+        if loss_by_name::<f32>(&self.model.loss).is_none() {
+            errors.push(FieldError::new(
+                "model.loss",
+                &format!("unknown loss {}", self.model.loss),
+            ));
+        }
+
+        if self.optimizer.name != "rmsprop" {
+            errors.push(FieldError::new(
+                "optimizer.name",
+                &format!("unknown optimizer {}", self.optimizer.name),
+            ));
+        }
+
+        if !(0.0..1.0).contains(&self.optimizer.decay_rate) {
+            errors.push(FieldError::new("optimizer.decay_rate", "must be in [0, 1)"));
+        }
+
+        if self.fit.epochs == 0 {
+            errors.push(FieldError::new("fit.epochs", "must be positive"));
+        }
+
+        if self.fit.batch_size == 0 {
+            errors.push(FieldError::new("fit.batch_size", "must be positive"));
+        }
+
+        if !(self.fit.learning_rate > 0.0 && self.fit.learning_rate.is_finite()) {
+            errors.push(FieldError::new(
+                "fit.learning_rate",
+                "must be a positive number",
+            ));
+        }
+
+        if let Some((field, message)) = self.fit.schedule.invalid_field() {
+            errors.push(FieldError::new(&format!("fit.schedule.{}", field), message));
+        }
+
+        for (i, name) in self.metrics.iter().enumerate() {
+            if metric_by_name(name).is_none() {
+                errors.push(FieldError::new(
+                    &format!("metrics[{}]", i),
+                    &format!("unknown metric {}", name),
+                ));
+            }
+        }
+
+        if self.output.is_empty() {
+            errors.push(FieldError::new("output", "missing the model path"));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+
+    pub fn metric_kinds(&self) -> Result<Vec<MetricKind>, ConfigError> {
+        self.validate()?;
+
+        Ok(self
+            .metrics
+            .iter()
+            .filter_map(|name| metric_by_name(name))
+            .collect())
+    }
+
+    // Layers of the config between the inputs and the targets. The output layer
+    // gets one unit per target when its units are left out.
+    pub fn build_model<T: Float>(
+        &self,
+        inputs: usize,
+        targets: usize,
+    ) -> Result<Model<T>, ConfigError> {
+        self.validate()?;
+
+        let mut layers = Vec::with_capacity(self.model.layers.len());
+        let mut input_dim = inputs;
+
+        let unknown = |path: String, message: String| {
+            ConfigError::Invalid(vec![FieldError { path, message }])
+        };
+
+        for (i, layer) in self.model.layers.iter().enumerate() {
+            let units = layer.units.unwrap_or(targets);
+            let activation = activation_by_name(&layer.activation).ok_or_else(|| {
+                unknown(
+                    format!("model.layers[{}].activation", i),
+                    format!("unknown activation {}", layer.activation),
+                )
+            })?;
+
+            layers.push(Layer::with_activation(activation, input_dim, units));
+
+            input_dim = units;
+        }
+
+        let loss = loss_by_name(&self.model.loss).ok_or_else(|| {
+            unknown(
+                "model.loss".to_string(),
+                format!("unknown loss {}", self.model.loss),
+            )
+        })?;
+
+        Ok(Model::with_loss(layers, loss))
+    }
+
+    // The config with the units of the output layer filled in
+    pub fn effective(&self, targets: usize) -> Self {
+        let mut config = self.clone();
+
+        if let Some(layer) = config.model.layers.last_mut() {
+            layer.units.get_or_insert(targets);
+        }
+
+        config
+    }
+
+    // <model stem>.config.json next to the model, so the runs saving their
+    // models in the same directory keep their own config
+    pub fn effective_config_path(&self) -> PathBuf {
+        let output = Path::new(&self.output);
+        let stem = output
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "model".to_string());
+
+        output.with_file_name(format!("{}.config.json", stem))
+    }
+}
@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        config::{ConfigError, ConfigFormat, DataFormat, FieldError, LayerConfig, TrainingConfig},
+        optimizers::schedule::LearningRateSchedule,
+    };
+
+    #[test]
+    fn test_formats_read_the_same_config() {
+        let json = r#"{
+            "data": {"train": "train.csv", "format": "mnist"},
+            "model": {
+                "layers": [{"units": 32, "activation": "relu"}, {"activation": "softmax"}]
+            },
+            "fit": {
+                "epochs": 2,
+                "learning_rate": 0.01,
+                "schedule": {"kind": "step", "every": 5, "factor": 0.5}
+            },
+            "metrics": ["accuracy", "top-2-accuracy"]
+        }"#;
+        let toml = r#"
+            metrics = ["accuracy", "top-2-accuracy"]
+
+            [data]
+            train = "train.csv"
+            format = "mnist"
+
+            [[model.layers]]
+            units = 32
+            activation = "relu"
+
+            [[model.layers]]
+            activation = "softmax"
+
+            [fit]
+            epochs = 2
+            learning_rate = 0.01
+
+            [fit.schedule]
+            kind = "step"
+            every = 5
+            factor = 0.5
+        "#;
+        let yaml = "
+data:
+  train: train.csv
+  format: mnist
+model:
+  layers:
+    - units: 32
+      activation: relu
+    - activation: softmax
+fit:
+  epochs: 2
+  learning_rate: 0.01
+  schedule:
+    kind: step
+    every: 5
+    factor: 0.5
+metrics: [accuracy, top-2-accuracy]
+";
+
+        let config = TrainingConfig::parse(json, ConfigFormat::Json).unwrap();
+
+        assert_eq!("train.csv", config.data.train);
+        assert_eq!(DataFormat::Mnist, config.data.format);
+        assert_eq!(
+            vec![
+                LayerConfig::new(Some(32), "relu"),
+                LayerConfig::new(None, "softmax")
+            ],
+            config.model.layers
+        );
+        assert_eq!(2, config.fit.epochs);
+        // The missing fields keep the defaults
+        assert_eq!(64, config.fit.batch_size);
+        assert_eq!(
+            LearningRateSchedule::Step {
+                every: 5,
+                factor: 0.5
+            },
+            config.fit.schedule
+        );
+        assert_eq!("categorical_crossentropy", config.model.loss);
+
+        assert_eq!(
+            config,
+            TrainingConfig::parse(toml, ConfigFormat::Toml).unwrap()
+        );
+        assert_eq!(
+            config,
+            TrainingConfig::parse(yaml, ConfigFormat::Yaml).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors_have_field_paths() {
+        let field_error = |text: &str| match TrainingConfig::parse(text, ConfigFormat::Json) {
+            Err(ConfigError::Field(error)) => error,
+            result => panic!("Expected a field error, got {:?}", result),
+        };
+
+        assert_eq!(
+            "model.layers[1].units",
+            field_error(
+                r#"{"model": {"layers": [{"units": 8, "activation": "relu"},
+                {"units": "ten", "activation": "softmax"}]}}"#
+            )
+            .path
+        );
+        assert_eq!("fit.epoch", field_error(r#"{"fit": {"epoch": 3}}"#).path);
+        assert_eq!(
+            "fit.schedule.kind",
+            field_error(r#"{"fit": {"schedule": {"kind": "cosine"}}}"#).path
+        );
+
+        let config = r#"
+            [model]
+            loss = "hinge"
+
+            [[model.layers]]
+            activation = "relu"
+
+            [[model.layers]]
+            units = 0
+            activation = "softmax"
+
+            [fit]
+            learning_rate = -1.0
+
+            [fit.schedule]
+            kind = "exponential"
+            gamma = 0.0
+        "#;
+
+        match TrainingConfig::parse(config, ConfigFormat::Toml) {
+            Err(ConfigError::Invalid(errors)) => assert_eq!(
+                vec![
+                    FieldError {
+                        path: "model.layers[0].units".to_string(),
+                        message: "only the output layer can leave out its units".to_string()
+                    },
+                    FieldError {
+                        path: "model.layers[1].units".to_string(),
+                        message: "must be positive".to_string()
+                    },
+                    FieldError {
+                        path: "model.loss".to_string(),
+                        message: "unknown loss hinge".to_string()
+                    },
+                    FieldError {
+                        path: "fit.learning_rate".to_string(),
+                        message: "must be a positive number".to_string()
+                    },
+                    FieldError {
+                        path: "fit.schedule.gamma".to_string(),
+                        message: "must be a positive number".to_string()
+                    },
+                ],
+                errors
+            ),
+            result => panic!("Expected invalid fields, got {:?}", result),
+        }
+
+        assert!(matches!(
+            TrainingConfig::load("config.ini"),
+            Err(ConfigError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_effective_config_round_trip() {
+        let mut config = TrainingConfig::default();
+
+        config.metrics.push("label-f1[2]@0.5".to_string());
+        config.fit.schedule = LearningRateSchedule::Exponential { gamma: 0.9 };
+
+        let effective = config.effective(10);
+
+        assert_eq!(Some(10), effective.model.layers[2].units);
+
+        for extension in ["json", "toml", "yaml"] {
+            let path =
+                std::env::temp_dir().join(format!("neura_rust_effective_config.{}", extension));
+
+            effective.save(&path).unwrap();
+
+            assert_eq!(effective, TrainingConfig::load(&path).unwrap());
+
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_effective_config_is_named_after_the_model() {
+        let config = TrainingConfig {
+            output: "./runs/small.json".to_string(),
+            ..TrainingConfig::default()
+        };
+
+        assert_eq!(
+            std::path::Path::new("./runs/small.config.json"),
+            config.effective_config_path()
+        );
+    }
+
+    #[test]
+    fn test_build_model() {
+        let model = TrainingConfig::default()
+            .build_model::<f32>(784, 10)
+            .unwrap();
+
+        assert_eq!(
+            vec![(784, 1024), (1024, 512), (512, 10)],
+            model
+                .get_layers_reference()
+                .iter()
+                .map(|layer| (layer.get_input_dim(), layer.get_output_dim()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use nalgebra::DMatrix;
+
+use crate::{
+    autograd::tape::{Tape, Var},
+    error::{check_shape, NeuraError},
+};
+
+use super::float::Float;
+
+#[derive(Clone, Copy)]
+// The name is the registry one, it is None for custom functions
+pub enum Activation<T: Float = f32> {
+    // Activation with a hand written derivative, either element-wise or its Jacobian
+    Explicit {
+        function: fn(&DMatrix<T>) -> DMatrix<T>,
+        derivative: fn(&DMatrix<T>) -> DMatrix<T>,
+        name: Option<&'static str>,
+    },
+    // Activation defined only by its forward pass, differentiated on the tape
+    Tape {
+        function: fn(&Var<T>) -> Var<T>,
+        name: Option<&'static str>,
+    },
+}
+
+impl<T: Float> Activation<T> {
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Activation::Explicit { name, .. } | Activation::Tape { name, .. } => *name,
+        }
+    }
+
+    pub(crate) fn named(mut self, registry_name: &'static str) -> Self {
+        match &mut self {
+            Activation::Explicit { name, .. } | Activation::Tape { name, .. } => {
+                *name = Some(registry_name)
+            }
+        }
+
+        self
+    }
+
+    // Tape activations must keep the shape of the raw output
+    pub fn forward(&self, raw_output: &DMatrix<T>) -> Result<DMatrix<T>, NeuraError> {
+        match self {
+            Activation::Explicit { function, .. } => Ok(function(raw_output)),
+            Activation::Tape { function, .. } => {
+                let tape = Tape::new();
+                let activated = function(&tape.variable(raw_output.clone())).value();
+
+                tape.check()?;
+                check_shape("activated output", raw_output.shape(), &activated)?;
+
+                Ok(activated)
+            }
+        }
+    }
+
+    // Gradient of the loss with respect to the raw output, given its gradient
+    // with respect to the activated output
+    pub fn backward(
+        &self,
+        raw_output: &DMatrix<T>,
+        output_gradient: &DMatrix<T>,
+    ) -> Result<DMatrix<T>, NeuraError> {
+        Ok(match self {
+            Activation::Explicit { derivative, .. } => {
+                let derivative = derivative(raw_output);
+
+                // Activations like softmax return a Jacobian instead of element-wise derivatives
+                if derivative.shape() == output_gradient.shape() {
+                    derivative.component_mul(output_gradient)
+                } else {
+                    derivative.transpose() * output_gradient
+                }
+            }
+            Activation::Tape { function, .. } => {
+                let tape = Tape::new();
+                let raw = tape.variable(raw_output.clone());
+                let activated = function(&raw);
+
+                tape.gradients_with_seed(&activated, output_gradient.clone())?
+                    .wrt(&raw)
+            }
+        })
+    }
+
+    pub fn trace(&self, raw_output: &Var<T>) -> Var<T> {
+        match self {
+            Activation::Explicit {
+                function,
+                derivative,
+                ..
+            } => raw_output.map(*function, *derivative),
+            Activation::Tape { function, .. } => function(raw_output),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use crate::{
+    data::{dataset::Dataset, split::stratified_k_fold},
+    error::NeuraError,
+    functions::metric::Metric,
+};
+
+use super::{float::Float, model::Model};
+
+// Scores returned by Model::test on the validation subset of every fold
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossValidationReport {
+    pub folds: Vec<Vec<(String, f32)>>,
+}
+
+impl CrossValidationReport {
+    // (metric, mean, std) over the folds, in the order of the scores
+    pub fn summary(&self) -> Vec<(String, f32, f32)> {
+        let Some(first_fold) = self.folds.first() else {
+            return Vec::new();
+        };
+
+        first_fold
+            .iter()
+            .enumerate()
+            .map(|(i, (metric, _))| {
+                let scores: Vec<f32> = self.folds.iter().map(|fold| fold[i].1).collect();
+
+                let mean = scores.iter().sum::<f32>() / scores.len() as f32;
+                let variance = scores
+                    .iter()
+                    .map(|score| (score - mean).powi(2))
+                    .sum::<f32>()
+                    / scores.len() as f32;
+
+                (metric.clone(), mean, variance.sqrt())
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for CrossValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (metric, mean, std) in self.summary() {
+            writeln!(f, "{}: {:.4} ± {:.4}", metric, mean, std)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Trains a fresh model from the factory on the train subset of every stratified
+// fold and tests it with fresh metrics on the remaining samples. The train closure
+// usually calls fit, and can count its calls to report the progress.
+pub fn cross_validate<T: Float>(
+    dataset: &dyn Dataset<T>,
+    folds: usize,
+    seed: u64,
+    metrics: impl Fn() -> Vec<Box<dyn Metric<T>>>,
+    mut model_factory: impl FnMut() -> Model<T>,
+    mut train: impl FnMut(&mut Model<T>, &dyn Dataset<T>) -> Result<(), NeuraError>,
+) -> Result<CrossValidationReport, NeuraError> {
+    let mut report = CrossValidationReport { folds: Vec::new() };
+
+    for (train_subset, validation_subset) in stratified_k_fold(dataset, folds, seed)?.iter() {
+        let mut model = model_factory();
+
+        train(&mut model, train_subset)?;
+
+        report.folds.push(model.test(metrics(), validation_subset)?);
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        core::{
+            cross_validation::{cross_validate, CrossValidationReport},
+            layer::Layer,
+            model::{FitOptions, Model},
+        },
+        data::dataset::InMemoryDataset,
+        functions::{
+            activations::{softmax, softmax_derivative},
+            losses::{categorical_crossentropy, categorical_crossentropy_derivative},
+            metric::MetricKind,
+        },
+        optimizers::rmsprop::RMSProp,
+    };
+
+    #[test]
+    fn test_summary() {
+        let report = CrossValidationReport {
+            folds: vec![
+                vec![("loss".to_string(), 1.0), ("accuracy".to_string(), 0.5)],
+                vec![("loss".to_string(), 3.0), ("accuracy".to_string(), 0.5)],
+            ],
+        };
+
+        assert_eq!(
+            vec![
+                ("loss".to_string(), 2.0, 1.0),
+                ("accuracy".to_string(), 0.5, 0.0)
+            ],
+            report.summary()
+        );
+        assert_eq!(
+            "loss: 2.0000 ± 1.0000\naccuracy: 0.5000 ± 0.0000\n",
+            report.to_string()
+        );
+    }
+
+    #[test]
+    fn test_cross_validate_builds_a_model_per_fold() {
+        let x = (0..12)
+            .map(|i| DMatrix::from_vec(2, 1, vec![(i % 2) as f32, 1.0 - (i % 2) as f32]))
+            .collect();
+        let y = (0..12)
+            .map(|i| DMatrix::from_vec(2, 1, vec![(i % 2) as f32, 1.0 - (i % 2) as f32]))
+            .collect();
+
+        let dataset = InMemoryDataset::new(x, y).unwrap();
+
+        let mut models = 0;
+
+        let report = cross_validate(
+            &dataset,
+            3,
+            0,
+            || vec![MetricKind::Accuracy.into()],
+            || {
+                models += 1;
+
+                Model::new(
+                    vec![Layer::new(softmax, softmax_derivative, 2, 2)],
+                    categorical_crossentropy,
+                    categorical_crossentropy_derivative,
+                )
+            },
+            |model, train| {
+                model.fit(
+                    FitOptions {
+                        batch_size: 4,
+                        epochs: 1,
+                        learning_rate: 0.01,
+                        ..FitOptions::default()
+                    },
+                    &mut RMSProp::new(0.9),
+                    train,
+                )
+            },
+        )
+        .unwrap();
+
+        assert_eq!(3, models);
+        assert_eq!(3, report.folds.len());
+        assert_eq!(
+            vec!["loss", "accuracy"],
+            report
+                .summary()
+                .iter()
+                .map(|(metric, _, _)| metric.as_str())
+                .collect::<Vec<&str>>()
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use std::{error::Error, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NonFiniteAction {
+    // Stop the training and return a DivergenceError
+    Abort,
+    // Discard the gradients of the batch and keep training
+    SkipBatch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DivergenceError {
+    NonFiniteLoss {
+        epoch: usize,
+        batch: usize,
+    },
+    NonFiniteGradients {
+        epoch: usize,
+        batch: usize,
+        layer: usize,
+    },
+}
+
+impl fmt::Display for DivergenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DivergenceError::NonFiniteLoss { epoch, batch } => write!(
+                f,
+                "Training diverged: non-finite loss at epoch {} (batch {})",
+                epoch, batch
+            ),
+            DivergenceError::NonFiniteGradients {
+                epoch,
+                batch,
+                layer,
+            } => write!(
+                f,
+                "Training diverged: non-finite gradients in layer {} at epoch {} (batch {})",
+                layer, epoch, batch
+            ),
+        }
+    }
+}
+
+impl Error for DivergenceError {}
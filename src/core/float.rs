@@ -0,0 +1,30 @@
+use std::fmt::{Debug, Display};
+
+use nalgebra::RealField;
+
+// Element type of the matrices of layers, models, losses, metrics and optimizers
+pub trait Float: RealField + Copy + Debug + Display + Send + Sync + 'static {
+    fn cast(value: f64) -> Self;
+
+    fn as_f64(self) -> f64;
+}
+
+impl Float for f32 {
+    fn cast(value: f64) -> Self {
+        value as f32
+    }
+
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Float for f64 {
+    fn cast(value: f64) -> Self {
+        value
+    }
+
+    fn as_f64(self) -> f64 {
+        self
+    }
+}
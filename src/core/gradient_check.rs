@@ -0,0 +1,159 @@
+use nalgebra::DMatrix;
+
+use crate::error::NeuraError;
+
+use super::{float::Float, model::Model};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parameter {
+    Weight { row: usize, column: usize },
+    Bias { row: usize },
+}
+
+// Weight and bias gradients of each layer
+type Gradients<T> = (Vec<DMatrix<T>>, Vec<DMatrix<T>>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientCheckReport<T: Float = f32> {
+    pub max_relative_error: T,
+    // Location of the parameter with the biggest relative error
+    pub layer: usize,
+    pub parameter: Parameter,
+}
+
+// Compares the gradients computed by the model backpropagation against central
+// finite differences, (loss(p + eps) - loss(p - eps)) / 2eps, for every parameter.
+pub fn gradient_check<T: Float>(
+    model: &mut Model<T>,
+    x: &DMatrix<T>,
+    y: &DMatrix<T>,
+    eps: T,
+) -> Result<GradientCheckReport<T>, NeuraError> {
+    let (weights_gradients, biases_gradients) = analytic_gradients(model, x, y)?;
+
+    let mut report = GradientCheckReport {
+        max_relative_error: T::zero(),
+        layer: 0,
+        parameter: Parameter::Bias { row: 0 },
+    };
+
+    for layer_index in 0..weights_gradients.len() {
+        let weights_gradient = &weights_gradients[layer_index];
+
+        for column in 0..weights_gradient.ncols() {
+            for row in 0..weights_gradient.nrows() {
+                let numerical = numerical_gradient(model, x, y, eps, |model| {
+                    &mut model.get_layers_mut_reference()[layer_index].get_weights_mut_reference()
+                        [(row, column)]
+                })?;
+
+                update_report(
+                    &mut report,
+                    relative_error(weights_gradient[(row, column)], numerical),
+                    layer_index,
+                    Parameter::Weight { row, column },
+                );
+            }
+        }
+
+        let biases_gradient = &biases_gradients[layer_index];
+
+        for row in 0..biases_gradient.nrows() {
+            let numerical = numerical_gradient(model, x, y, eps, |model| {
+                &mut model.get_layers_mut_reference()[layer_index].get_biases_mut_reference()[row]
+            })?;
+
+            update_report(
+                &mut report,
+                relative_error(biases_gradient[row], numerical),
+                layer_index,
+                Parameter::Bias { row },
+            );
+        }
+    }
+
+    Ok(report)
+}
+
+fn analytic_gradients<T: Float>(
+    model: &mut Model<T>,
+    x: &DMatrix<T>,
+    y: &DMatrix<T>,
+) -> Result<Gradients<T>, NeuraError> {
+    model
+        .get_layers_mut_reference()
+        .iter_mut()
+        .for_each(|layer| layer.clear_error_and_delta());
+
+    let prediction = model.forward(x)?;
+
+    model.backpropagation(y, x, &prediction, T::one())?;
+
+    let layers = model.get_layers_mut_reference();
+
+    let gradients = (
+        layers
+            .iter()
+            .map(|layer| layer.get_errors_clone())
+            .collect(),
+        layers
+            .iter()
+            .map(|layer| layer.get_deltas_clone())
+            .collect(),
+    );
+
+    layers
+        .iter_mut()
+        .for_each(|layer| layer.clear_error_and_delta());
+
+    Ok(gradients)
+}
+
+fn numerical_gradient<T: Float>(
+    model: &mut Model<T>,
+    x: &DMatrix<T>,
+    y: &DMatrix<T>,
+    eps: T,
+    parameter: impl Fn(&mut Model<T>) -> &mut T,
+) -> Result<T, NeuraError> {
+    let original = *parameter(model);
+
+    *parameter(model) = original + eps;
+    let prediction = model.forward(x)?;
+    let loss_plus = model.calculate_loss(y, &prediction)?;
+
+    *parameter(model) = original - eps;
+    let prediction = model.forward(x)?;
+    let loss_minus = model.calculate_loss(y, &prediction)?;
+
+    *parameter(model) = original;
+
+    Ok((loss_plus - loss_minus) / (T::cast(2.0) * eps))
+}
+
+fn relative_error<T: Float>(analytic: T, numerical: T) -> T {
+    let scale = analytic.abs().max(numerical.abs());
+
+    // Both gradients are practically zero
+    if scale < T::cast(1e-6) {
+        return T::zero();
+    }
+
+    (analytic - numerical).abs() / scale
+}
+
+fn update_report<T: Float>(
+    report: &mut GradientCheckReport<T>,
+    error: T,
+    layer: usize,
+    parameter: Parameter,
+) {
+    // A NaN error must also be reported
+    if error.as_f64().is_nan() || error > report.max_relative_error {
+        *report = GradientCheckReport {
+            max_relative_error: error,
+            layer,
+            parameter,
+        };
+    }
+}
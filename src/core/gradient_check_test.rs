@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        autograd::tape::Var,
+        core::{float::Float, gradient_check::gradient_check, layer::Layer, model::Model},
+        functions::{
+            activations::{
+                relu, relu_derivative, relu_on_tape, sigmoid, sigmoid_derivative, sigmoid_on_tape,
+                softmax, softmax_derivative, softmax_on_tape,
+            },
+            losses::{
+                binary_crossentropy, binary_crossentropy_derivative, binary_crossentropy_on_tape,
+                categorical_crossentropy, categorical_crossentropy_derivative,
+                categorical_crossentropy_on_tape, mse, mse_derivative, mse_on_tape, squared_error,
+                squared_error_derivative, squared_error_on_tape,
+            },
+        },
+    };
+
+    type Activation<T> = (
+        &'static str,
+        fn(&DMatrix<T>) -> DMatrix<T>,
+        fn(&DMatrix<T>) -> DMatrix<T>,
+        fn(&Var<T>) -> Var<T>,
+    );
+    type Loss<T> = (
+        &'static str,
+        fn(&DMatrix<T>, &DMatrix<T>) -> T,
+        fn(&DMatrix<T>, &DMatrix<T>) -> DMatrix<T>,
+        fn(&Var<T>, &Var<T>) -> Var<T>,
+    );
+
+    fn activations<T: Float>() -> [Activation<T>; 3] {
+        [
+            ("sigmoid", sigmoid, sigmoid_derivative, sigmoid_on_tape),
+            ("relu", relu, relu_derivative, relu_on_tape),
+            ("softmax", softmax, softmax_derivative, softmax_on_tape),
+        ]
+    }
+
+    fn losses<T: Float>() -> [Loss<T>; 4] {
+        [
+            ("mse", mse, mse_derivative, mse_on_tape),
+            (
+                "squared_error",
+                squared_error,
+                squared_error_derivative,
+                squared_error_on_tape,
+            ),
+            (
+                "categorical_crossentropy",
+                categorical_crossentropy,
+                categorical_crossentropy_derivative,
+                categorical_crossentropy_on_tape,
+            ),
+            (
+                "binary_crossentropy",
+                binary_crossentropy,
+                binary_crossentropy_derivative,
+                binary_crossentropy_on_tape,
+            ),
+        ]
+    }
+
+    // Small deterministic weights and positive biases keep the relu units away from
+    // their kink and every output inside (0, 1), so all the losses are defined.
+    fn layer<T: Float>(activation: &Activation<T>, input_dim: usize, neurons: usize) -> Layer<T> {
+        let (biases, weights) = params(input_dim, neurons);
+
+        Layer::from(activation.1, activation.2, biases, weights)
+    }
+
+    fn layer_on_tape<T: Float>(
+        activation: &Activation<T>,
+        input_dim: usize,
+        neurons: usize,
+    ) -> Layer<T> {
+        let (biases, weights) = params(input_dim, neurons);
+
+        Layer::from_on_tape(activation.3, biases, weights)
+    }
+
+    fn params<T: Float>(input_dim: usize, neurons: usize) -> (DMatrix<T>, DMatrix<T>) {
+        let weights = DMatrix::from_fn(neurons, input_dim, |row, column| {
+            T::cast(0.2 * ((row * input_dim + column) as f64 * 1.7).sin())
+        });
+
+        (DMatrix::from_element(neurons, 1, T::cast(0.3)), weights)
+    }
+
+    fn check_every_activation_and_loss<T: Float>(eps: T, tolerance: T) {
+        let x = DMatrix::from_vec(3, 1, vec![T::cast(0.5), T::cast(-0.3), T::cast(0.8)]);
+        let y = DMatrix::from_vec(3, 1, vec![T::zero(), T::one(), T::zero()]);
+
+        for hidden_activation in activations::<T>().iter() {
+            for output_activation in activations::<T>().iter() {
+                for loss in losses::<T>().iter() {
+                    let models = [
+                        (
+                            "explicit",
+                            Model::new(
+                                vec![
+                                    layer(hidden_activation, 3, 4),
+                                    layer(output_activation, 4, 3),
+                                ],
+                                loss.1,
+                                loss.2,
+                            ),
+                        ),
+                        (
+                            "tape activations",
+                            Model::new(
+                                vec![
+                                    layer_on_tape(hidden_activation, 3, 4),
+                                    layer_on_tape(output_activation, 4, 3),
+                                ],
+                                loss.1,
+                                loss.2,
+                            ),
+                        ),
+                        (
+                            "tape backpropagation",
+                            Model::new_on_tape(
+                                vec![
+                                    layer(hidden_activation, 3, 4),
+                                    layer_on_tape(output_activation, 4, 3),
+                                ],
+                                loss.3,
+                            ),
+                        ),
+                    ];
+
+                    for (kind, mut model) in models {
+                        let report = gradient_check(&mut model, &x, &y, eps).unwrap();
+
+                        assert!(
+                            report.max_relative_error < tolerance,
+                            "{} -> {} with {} ({}): {:?}",
+                            hidden_activation.0,
+                            output_activation.0,
+                            loss.0,
+                            kind,
+                            report
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gradients_of_every_activation_and_loss() {
+        check_every_activation_and_loss::<f32>(1e-2, 2e-2);
+    }
+
+    #[test]
+    fn test_gradients_of_every_activation_and_loss_in_double_precision() {
+        check_every_activation_and_loss::<f64>(1e-5, 1e-6);
+    }
+
+    #[test]
+    fn test_wrong_derivative_is_reported() {
+        let x = DMatrix::from_vec(3, 1, vec![0.5, -0.3, 0.8]);
+        let y = DMatrix::from_vec(3, 1, vec![0.0, 1.0, 0.0]);
+
+        let mut model = Model::new(
+            vec![layer(&activations::<f32>()[0], 3, 3)],
+            mse,
+            |expected, predicted| predicted - expected,
+        );
+
+        let report = gradient_check(&mut model, &x, &y, 1e-2).unwrap();
+
+        assert!(report.max_relative_error > 0.4, "{:?}", report);
+    }
+}
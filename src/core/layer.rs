@@ -4,25 +4,94 @@ use rand_distr::{Distribution, Normal};
 
 use nalgebra::DMatrix;
 
-pub struct Layer {
-    activation: fn(&DMatrix<f32>) -> DMatrix<f32>,
-    activation_derivative: fn(&DMatrix<f32>) -> DMatrix<f32>,
-    biases: DMatrix<f32>,
-    deltas: DMatrix<f32>,
-    errors: DMatrix<f32>,
-    last_activated_output: DMatrix<f32>,
-    last_raw_output: DMatrix<f32>,
-    optimizer_params: HashMap<String, DMatrix<f32>>,
-    weights: DMatrix<f32>,
+use crate::{
+    autograd::tape::Var,
+    error::{check_shape, NeuraError},
+};
+
+use super::{activation::Activation, float::Float};
+
+pub struct Layer<T: Float = f32> {
+    activation: Activation<T>,
+    biases: DMatrix<T>,
+    deltas: DMatrix<T>,
+    errors: DMatrix<T>,
+    last_activated_output: DMatrix<T>,
+    last_raw_output: DMatrix<T>,
+    optimizer_params: HashMap<String, DMatrix<T>>,
+    weights: DMatrix<T>,
 }
 
-impl Layer {
+impl<T: Float> Layer<T> {
     pub fn new(
-        activation: fn(&DMatrix<f32>) -> DMatrix<f32>,
-        activation_derivative: fn(&DMatrix<f32>) -> DMatrix<f32>,
+        activation: fn(&DMatrix<T>) -> DMatrix<T>,
+        activation_derivative: fn(&DMatrix<T>) -> DMatrix<T>,
         input_dim: usize,
         neurons: usize,
     ) -> Self {
+        Self::with_random_params(
+            Activation::Explicit {
+                function: activation,
+                derivative: activation_derivative,
+                name: None,
+            },
+            input_dim,
+            neurons,
+        )
+    }
+
+    pub fn new_on_tape(
+        activation: fn(&Var<T>) -> Var<T>,
+        input_dim: usize,
+        neurons: usize,
+    ) -> Self {
+        Self::with_random_params(
+            Activation::Tape {
+                function: activation,
+                name: None,
+            },
+            input_dim,
+            neurons,
+        )
+    }
+
+    pub fn from(
+        activation: fn(&DMatrix<T>) -> DMatrix<T>,
+        activation_derivative: fn(&DMatrix<T>) -> DMatrix<T>,
+        biases: DMatrix<T>,
+        weights: DMatrix<T>,
+    ) -> Self {
+        Self::with_params(
+            Activation::Explicit {
+                function: activation,
+                derivative: activation_derivative,
+                name: None,
+            },
+            biases,
+            weights,
+        )
+    }
+
+    pub fn from_on_tape(
+        activation: fn(&Var<T>) -> Var<T>,
+        biases: DMatrix<T>,
+        weights: DMatrix<T>,
+    ) -> Self {
+        Self::with_params(
+            Activation::Tape {
+                function: activation,
+                name: None,
+            },
+            biases,
+            weights,
+        )
+    }
+
+    pub fn with_activation(activation: Activation<T>, input_dim: usize, neurons: usize) -> Self {
+        Self::with_random_params(activation, input_dim, neurons)
+    }
+
+    fn with_random_params(activation: Activation<T>, input_dim: usize, neurons: usize) -> Self {
         // let mut r = StdRng::seed_from_u64(222);
 
         let normal = Normal::new(0.0_f32, 1.0_f32).unwrap();
@@ -34,37 +103,31 @@ impl Layer {
 
         let std = (2.0_f32 / input_dim as f32).sqrt();
 
-        let weights: Vec<f32> = weights_sample.iter().map(|x| x * std).collect();
+        let weights: Vec<T> = weights_sample
+            .iter()
+            .map(|x| T::cast((x * std) as f64))
+            .collect();
 
         let biases_sample: Vec<f32> = normal
             .sample_iter(&mut rand::thread_rng())
             .take(neurons)
             .collect();
 
-        let biases: Vec<f32> = biases_sample.iter().map(|x| x * std).collect();
+        let biases: Vec<T> = biases_sample
+            .iter()
+            .map(|x| T::cast((x * std) as f64))
+            .collect();
 
-        Self {
+        Self::with_params(
             activation,
-            activation_derivative,
-            biases: DMatrix::from_row_slice(neurons, 1, &biases),
-            deltas: DMatrix::zeros(neurons, 1),
-            errors: DMatrix::zeros(neurons, input_dim),
-            last_activated_output: DMatrix::identity(1, 1),
-            last_raw_output: DMatrix::identity(1, 1),
-            optimizer_params: HashMap::new(),
-            weights: DMatrix::from_row_slice(neurons, input_dim, &weights),
-        }
+            DMatrix::from_row_slice(neurons, 1, &biases),
+            DMatrix::from_row_slice(neurons, input_dim, &weights),
+        )
     }
 
-    pub fn from(
-        activation: fn(&DMatrix<f32>) -> DMatrix<f32>,
-        activation_derivative: fn(&DMatrix<f32>) -> DMatrix<f32>,
-        biases: DMatrix<f32>,
-        weights: DMatrix<f32>,
-    ) -> Self {
+    pub fn with_params(activation: Activation<T>, biases: DMatrix<T>, weights: DMatrix<T>) -> Self {
         Self {
             activation,
-            activation_derivative,
             biases,
             deltas: DMatrix::zeros(weights.nrows(), 1),
             errors: DMatrix::zeros(weights.nrows(), weights.ncols()),
@@ -75,47 +138,101 @@ impl Layer {
         }
     }
 
-    pub fn forward(&mut self, data: &DMatrix<f32>) -> &DMatrix<f32> {
+    // The input is a column of input_dim values
+    pub fn forward(&mut self, data: &DMatrix<T>) -> Result<&DMatrix<T>, NeuraError> {
+        check_shape("layer input", (self.get_input_dim(), 1), data)?;
+
         self.last_raw_output = (&self.weights * data) + &self.biases;
 
-        self.last_activated_output = (self.activation)(&self.last_raw_output);
+        self.last_activated_output = self.activation.forward(&self.last_raw_output)?;
 
-        &self.last_activated_output
+        Ok(&self.last_activated_output)
+    }
+
+    // Like forward but without caching the outputs, for inference on a shared
+    // layer. The raw output is written to the given matrix.
+    pub fn infer(
+        &self,
+        data: &DMatrix<T>,
+        raw_output: &mut DMatrix<T>,
+    ) -> Result<DMatrix<T>, NeuraError> {
+        check_shape("layer input", (self.get_input_dim(), 1), data)?;
+
+        if raw_output.shape() == self.biases.shape() {
+            raw_output.copy_from(&self.biases);
+        } else {
+            *raw_output = self.biases.clone();
+        }
+
+        raw_output.gemm(T::one(), &self.weights, data, T::one());
+
+        self.activation.forward(raw_output)
+    }
+
+    // Infers every column of the inputs with a single matrix product. The
+    // activation still applies to each column on its own, like softmax needs.
+    pub fn infer_batch(&self, inputs: &DMatrix<T>) -> Result<DMatrix<T>, NeuraError> {
+        check_shape(
+            "layer inputs",
+            (self.get_input_dim(), inputs.ncols()),
+            inputs,
+        )?;
+
+        let mut raw_outputs = &self.weights * inputs;
+
+        for mut column in raw_outputs.column_iter_mut() {
+            column += self.biases.column(0);
+        }
+
+        let activated_outputs = (0..raw_outputs.ncols())
+            .map(|index| {
+                self.activation
+                    .forward(&raw_outputs.columns(index, 1).into_owned())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DMatrix::from_iterator(
+            self.get_output_dim(),
+            activated_outputs.len(),
+            activated_outputs
+                .iter()
+                .flat_map(|output| output.iter().copied()),
+        ))
     }
 
     pub fn propagate_error(
         &self,
         last_layer: bool,
-        next_layer_delta: &DMatrix<f32>,
-        next_layer_weights: &DMatrix<f32>,
-        previous_layer_output: &DMatrix<f32>,
-    ) -> (DMatrix<f32>, DMatrix<f32>) {
-        let activation_derivative = (self.activation_derivative)(&self.last_raw_output);
-
-        let deltas = if last_layer {
-            activation_derivative.component_mul(&next_layer_delta)
+        next_layer_delta: &DMatrix<T>,
+        next_layer_weights: &DMatrix<T>,
+        previous_layer_output: &DMatrix<T>,
+    ) -> Result<(DMatrix<T>, DMatrix<T>), NeuraError> {
+        let output_gradient = if last_layer {
+            next_layer_delta.clone()
         } else {
-            let next_layer_weighted_error = next_layer_weights.transpose() * next_layer_delta;
-
-            next_layer_weighted_error.component_mul(&activation_derivative)
+            next_layer_weights.transpose() * next_layer_delta
         };
 
-        (&deltas * previous_layer_output.transpose(), deltas)
+        let deltas = self
+            .activation
+            .backward(&self.last_raw_output, &output_gradient)?;
+
+        Ok((&deltas * previous_layer_output.transpose(), deltas))
     }
 
-    pub fn sum_errors_and_deltas(&mut self, deltas: &DMatrix<f32>, errors: &DMatrix<f32>) {
+    pub fn sum_errors_and_deltas(&mut self, deltas: &DMatrix<T>, errors: &DMatrix<T>) {
         self.errors += errors;
         self.deltas += deltas;
     }
 
-    pub fn update_params(&mut self, learning_rate: f32, batch_size: usize) {
-        let mut error = &self.errors / batch_size as f32;
+    pub fn update_params(&mut self, learning_rate: T, batch_size: usize) {
+        let mut error = &self.errors / T::cast(batch_size as f64);
 
         error.scale_mut(learning_rate);
 
         self.weights -= error;
 
-        let mut deltas = &self.deltas / batch_size as f32;
+        let mut deltas = &self.deltas / T::cast(batch_size as f64);
 
         deltas.scale_mut(learning_rate);
 
@@ -129,35 +246,64 @@ impl Layer {
         self.errors = DMatrix::zeros(self.weights.nrows(), self.weights.ncols());
     }
 
-    pub fn get_optimizer_params_mut_reference(&mut self) -> &mut HashMap<String, DMatrix<f32>> {
+    pub fn clamp_errors_and_deltas(&mut self, limit: T) {
+        self.errors.apply(|x| *x = x.clamp(-limit, limit));
+        self.deltas.apply(|x| *x = x.clamp(-limit, limit));
+    }
+
+    pub fn scale_errors_and_deltas(&mut self, factor: T) {
+        self.errors.scale_mut(factor);
+        self.deltas.scale_mut(factor);
+    }
+
+    pub fn has_non_finite_gradients(&self) -> bool {
+        self.errors
+            .iter()
+            .chain(self.deltas.iter())
+            .any(|x| !x.is_finite())
+    }
+
+    pub fn get_squared_gradients_sum(&self) -> T {
+        self.errors.norm_squared() + self.deltas.norm_squared()
+    }
+
+    pub fn get_optimizer_params_mut_reference(&mut self) -> &mut HashMap<String, DMatrix<T>> {
         &mut self.optimizer_params
     }
 
-    pub fn get_optimizer_params_reference(&self) -> &HashMap<String, DMatrix<f32>> {
+    pub fn get_optimizer_params_reference(&self) -> &HashMap<String, DMatrix<T>> {
         &self.optimizer_params
     }
 
-    pub fn get_last_output(&self) -> &DMatrix<f32> {
+    pub fn get_last_output(&self) -> &DMatrix<T> {
         &self.last_activated_output
     }
 
-    pub fn get_deltas_clone(&self) -> DMatrix<f32> {
+    pub fn get_deltas_clone(&self) -> DMatrix<T> {
         self.deltas.clone()
     }
 
-    pub fn get_errors_clone(&self) -> DMatrix<f32> {
+    pub fn get_errors_clone(&self) -> DMatrix<T> {
         self.errors.clone()
     }
 
-    pub fn get_biases_mut_reference(&mut self) -> &mut DMatrix<f32> {
+    pub fn get_activation(&self) -> Activation<T> {
+        self.activation
+    }
+
+    pub fn get_biases_reference(&self) -> &DMatrix<T> {
+        &self.biases
+    }
+
+    pub fn get_biases_mut_reference(&mut self) -> &mut DMatrix<T> {
         &mut self.biases
     }
 
-    pub fn get_weights_mut_reference(&mut self) -> &mut DMatrix<f32> {
+    pub fn get_weights_mut_reference(&mut self) -> &mut DMatrix<T> {
         &mut self.weights
     }
 
-    pub fn get_weights_reference(&self) -> &DMatrix<f32> {
+    pub fn get_weights_reference(&self) -> &DMatrix<T> {
         &self.weights
     }
 
@@ -15,7 +15,7 @@ mod tests {
 
         let data = DMatrix::from_vec(2, 1, vec![0.0, 1.0]);
 
-        let r = layer.forward(&data).clone();
+        let r = layer.forward(&data).unwrap().clone();
 
         assert_eq!(r, Matrix3x1::new(1.5, 1.1, 1.7))
     }
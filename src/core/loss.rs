@@ -0,0 +1,104 @@
+use nalgebra::DMatrix;
+
+use crate::{
+    autograd::tape::{Tape, Var},
+    error::{check_shape, NeuraError},
+};
+
+use super::float::Float;
+
+#[derive(Clone, Copy)]
+// The name is the registry one, it is None for custom functions
+pub enum Loss<T: Float = f32> {
+    // Loss with a hand written derivative with respect to the prediction
+    Explicit {
+        function: fn(&DMatrix<T>, &DMatrix<T>) -> T,
+        derivative: fn(&DMatrix<T>, &DMatrix<T>) -> DMatrix<T>,
+        name: Option<&'static str>,
+    },
+    // Loss defined only by its forward pass (expected, predicted), differentiated on the tape
+    Tape {
+        function: fn(&Var<T>, &Var<T>) -> Var<T>,
+        name: Option<&'static str>,
+    },
+}
+
+impl<T: Float> Loss<T> {
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Loss::Explicit { name, .. } | Loss::Tape { name, .. } => *name,
+        }
+    }
+
+    pub(crate) fn named(mut self, registry_name: &'static str) -> Self {
+        match &mut self {
+            Loss::Explicit { name, .. } | Loss::Tape { name, .. } => *name = Some(registry_name),
+        }
+
+        self
+    }
+
+    // The prediction must have the shape of the target, a tape loss must be 1x1
+    pub fn evaluate(&self, expected: &DMatrix<T>, predicted: &DMatrix<T>) -> Result<T, NeuraError> {
+        check_shape("prediction", expected.shape(), predicted)?;
+
+        Ok(match self {
+            Loss::Explicit { function, .. } => function(expected, predicted),
+            Loss::Tape { function, .. } => {
+                let tape = Tape::new();
+                let loss = function(
+                    &tape.variable(expected.clone()),
+                    &tape.variable(predicted.clone()),
+                )
+                .value();
+
+                tape.check()?;
+                check_shape("loss", (1, 1), &loss)?;
+
+                loss[0]
+            }
+        })
+    }
+
+    pub fn derivative(
+        &self,
+        expected: &DMatrix<T>,
+        predicted: &DMatrix<T>,
+    ) -> Result<DMatrix<T>, NeuraError> {
+        check_shape("prediction", expected.shape(), predicted)?;
+
+        Ok(match self {
+            Loss::Explicit { derivative, .. } => derivative(expected, predicted),
+            Loss::Tape { function, .. } => {
+                let tape = Tape::new();
+                let predicted = tape.variable(predicted.clone());
+                let loss = function(&tape.variable(expected.clone()), &predicted);
+
+                tape.gradients(&loss)?.wrt(&predicted)
+            }
+        })
+    }
+
+    pub fn trace(&self, expected: &DMatrix<T>, predicted: &Var<T>) -> Result<Var<T>, NeuraError> {
+        match self {
+            Loss::Explicit {
+                function,
+                derivative,
+                ..
+            } => predicted.loss(expected, *function, *derivative),
+            Loss::Tape { function, .. } => {
+                let shape = predicted.shape();
+
+                if shape != expected.shape() {
+                    return Err(NeuraError::shape_mismatch(
+                        "prediction",
+                        expected.shape(),
+                        shape,
+                    ));
+                }
+
+                Ok(function(&predicted.constant(expected.clone()), predicted))
+            }
+        }
+    }
+}
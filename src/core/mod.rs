@@ -1,4 +1,17 @@
+pub mod activation;
+pub mod cross_validation;
+mod cross_validation_test;
+pub mod divergence;
+pub mod float;
+pub mod gradient_check;
+mod gradient_check_test;
 pub mod layer;
 mod layer_test;
+pub mod loss;
 pub mod model;
 mod model_test;
+pub mod reduced_precision;
+mod reduced_precision_test;
+pub mod serialization;
+mod serialization_test;
+pub mod workspace;
@@ -1,67 +1,182 @@
-use std::fmt::Write;
+use std::{borrow::Cow, fmt::Write};
 
 use indicatif::{ProgressBar, ProgressState, ProgressStyle};
 use nalgebra::DMatrix;
-use rand::{thread_rng, Rng};
 
-use crate::{functions::metrics::print_metrics, optimizers::optimizer::Optimizer};
+use crate::{
+    autograd::tape::{Tape, Var},
+    data::{
+        augmentation::Augmenter,
+        dataset::{sample_order, Dataset},
+        sampling::{samples_by_class, BatchSampler},
+        split::target_class,
+    },
+    error::{check_shape, NeuraError},
+    functions::{
+        metric::{print_metrics, Metric},
+        metrics::add_to_confusion_matrix,
+    },
+    optimizers::{
+        gradient_clipping::{clip_gradients, global_gradient_norm, GradientClipping},
+        optimizer::Optimizer,
+        schedule::LearningRateSchedule,
+    },
+    preprocessing::pipeline::Pipeline,
+};
+
+use super::{
+    divergence::{DivergenceError, NonFiniteAction},
+    float::Float,
+    layer::Layer,
+    loss::Loss,
+    workspace::Workspace,
+};
+
+// How fit trains, besides the optimizer and the dataset. The defaults are 10 epochs
+// of batches of 64 samples with a constant learning rate of 0.001, aborting on
+// divergence.
+pub struct FitOptions<'a, T: Float = f32> {
+    pub batch_size: usize,
+    pub epochs: usize,
+    // Learning rate of the first epoch, the schedule gives the next ones
+    pub learning_rate: T,
+    pub schedule: LearningRateSchedule,
+    // Reset every epoch and updated with the trained batches
+    pub metrics: Vec<Box<dyn Metric<T>>>,
+    pub gradient_clipping: Option<GradientClipping<T>>,
+    pub non_finite_action: NonFiniteAction,
+    pub augmenter: Option<&'a mut Augmenter>,
+    // One weight per sample of the dataset, multiplying the class weights
+    pub sample_weights: Option<&'a [T]>,
+    // Orders the samples of every epoch instead of shuffling them
+    pub sampler: Option<&'a mut BatchSampler>,
+}
 
-use super::layer::Layer;
+impl<T: Float> Default for FitOptions<'_, T> {
+    fn default() -> Self {
+        Self {
+            batch_size: 64,
+            epochs: 10,
+            learning_rate: T::cast(0.001),
+            schedule: LearningRateSchedule::Constant,
+            metrics: Vec::new(),
+            gradient_clipping: None,
+            non_finite_action: NonFiniteAction::Abort,
+            augmenter: None,
+            sample_weights: None,
+            sampler: None,
+        }
+    }
+}
 
-pub struct Model {
-    layers: Vec<Layer>,
-    loss: fn(&DMatrix<f32>, &DMatrix<f32>) -> f32,
-    loss_derivative: fn(&DMatrix<f32>, &DMatrix<f32>) -> DMatrix<f32>,
+pub struct Model<T: Float = f32> {
+    layers: Vec<Layer<T>>,
+    loss: Loss<T>,
+    pipeline: Option<Pipeline>,
+    class_weights: Option<Vec<T>>,
 }
 
-impl Model {
+impl<T: Float> Model<T> {
     pub fn new(
-        layers: Vec<Layer>,
-        loss: fn(&DMatrix<f32>, &DMatrix<f32>) -> f32,
-        loss_derivative: fn(&DMatrix<f32>, &DMatrix<f32>) -> DMatrix<f32>,
+        layers: Vec<Layer<T>>,
+        loss: fn(&DMatrix<T>, &DMatrix<T>) -> T,
+        loss_derivative: fn(&DMatrix<T>, &DMatrix<T>) -> DMatrix<T>,
     ) -> Self {
         Self {
             layers,
-            loss,
-            loss_derivative,
+            loss: Loss::Explicit {
+                function: loss,
+                derivative: loss_derivative,
+                name: None,
+            },
+            pipeline: None,
+            class_weights: None,
         }
     }
 
-    fn shuffle_dataset<T>(x: &mut [T], y: &mut [T]) {
-        let mut rng = thread_rng();
-        for i in (1..x.len()).rev() {
-            let j = rng.gen_range(0..=i);
-            x.swap(i, j);
-            y.swap(i, j);
+    // The backpropagation of models with a loss defined on the tape runs entirely on the tape
+    pub fn new_on_tape(layers: Vec<Layer<T>>, loss: fn(&Var<T>, &Var<T>) -> Var<T>) -> Self {
+        Self {
+            layers,
+            loss: Loss::Tape {
+                function: loss,
+                name: None,
+            },
+            pipeline: None,
+            class_weights: None,
+        }
+    }
+
+    pub fn with_loss(layers: Vec<Layer<T>>, loss: Loss<T>) -> Self {
+        Self {
+            layers,
+            loss,
+            pipeline: None,
+            class_weights: None,
         }
     }
 
     pub fn fit(
         &mut self,
-        batch_size: usize,
-        epochs: usize,
-        learning_rate: f32,
-        metrics: Vec<String>,
-        optimizer: &mut dyn Optimizer,
-        x: Vec<DMatrix<f32>>,
-        y: Vec<DMatrix<f32>>,
-    ) {
-        let mut x = x.clone();
-        let mut y = y.clone();
+        options: FitOptions<T>,
+        optimizer: &mut dyn Optimizer<T>,
+        dataset: &dyn Dataset<T>,
+    ) -> Result<(), NeuraError> {
+        let FitOptions {
+            batch_size,
+            epochs,
+            learning_rate,
+            schedule,
+            mut metrics,
+            gradient_clipping,
+            non_finite_action,
+            mut augmenter,
+            sample_weights,
+            mut sampler,
+        } = options;
+
+        if batch_size == 0 {
+            return Err(NeuraError::InvalidArgument(
+                "The batch size must be positive".to_string(),
+            ));
+        }
+
+        schedule.check()?;
+
+        if let Some(weights) = sample_weights {
+            if weights.len() != dataset.len() {
+                return Err(NeuraError::InvalidArgument(format!(
+                    "Got {} sample weights for a dataset of {} samples",
+                    weights.len(),
+                    dataset.len()
+                )));
+            }
+        }
+
+        let samples_by_class = sampler
+            .as_ref()
+            .map(|_| samples_by_class(dataset))
+            .transpose()?;
 
         self.layers
             .iter_mut()
             .for_each(|layer| optimizer.initialize_layer_additional_params(layer));
 
         for epoch in 0..epochs {
-            Self::shuffle_dataset(&mut x, &mut y);
+            metrics.iter_mut().for_each(|metric| metric.reset());
 
-            let batches = x.chunks(batch_size).zip(y.chunks(batch_size));
+            let epoch_learning_rate = schedule.learning_rate(learning_rate, epoch);
 
-            let mut epoch_predictions = Vec::with_capacity(x.len());
-            let mut epoch_loss = 0_f32;
+            let mut trained_samples = 0;
+            let mut epoch_loss = T::zero();
+            let mut skipped_batches = 0;
+
+            let order = match (sampler.as_deref_mut(), &samples_by_class) {
+                (Some(sampler), Some(samples_by_class)) => sampler.epoch_order(samples_by_class),
+                _ => sample_order(dataset.len(), true),
+            };
 
-            let batches_ammount = batches.len();
+            let batches_ammount = order.len().div_ceil(batch_size);
             let progress_bar = ProgressBar::new(batches_ammount as u64);
 
             progress_bar.set_style(ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] {msg} [{wide_bar:.cyan/blue}] {pos}/{len} ({eta})")
@@ -69,46 +184,188 @@ impl Model {
                 .with_key("eta", |state: &ProgressState, w: &mut dyn Write| write!(w, "{:.1}s", state.eta().as_secs_f32()).unwrap())
                 .progress_chars("#>-"));
 
-            for (i, (input_batch, target_batch)) in batches.enumerate() {
+            for (i, batch) in dataset
+                .batches_in_order(order.clone(), batch_size)
+                .enumerate()
+            {
                 progress_bar.inc(1);
 
-                let mut batch_loss = 0_f32;
+                let mut batch = match batch {
+                    Ok(batch) => batch,
+                    Err(error) => {
+                        progress_bar.abandon();
+
+                        return Err(error);
+                    }
+                };
+
+                if let Some(augmenter) = augmenter.as_deref_mut() {
+                    if let Err(error) = augmenter.augment_batch(&mut batch) {
+                        progress_bar.abandon();
+
+                        return Err(error);
+                    }
+                }
+
+                let mut batch_loss = T::zero();
+                let mut batch_predictions = Vec::with_capacity(batch.len());
+
+                let batch_indices = &order[i * batch_size..(i * batch_size + batch.len())];
+
+                for ((input_data, target_data), &index) in batch
+                    .inputs
+                    .iter()
+                    .zip(batch.targets.iter())
+                    .zip(batch_indices)
+                {
+                    let weight = self.class_weight(target_data)
+                        * sample_weights.map_or(T::one(), |weights| weights[index]);
+
+                    let (loss, prediction) =
+                        match self.train_sample(input_data, target_data, weight) {
+                            Ok(result) => result,
+                            Err(error) => {
+                                progress_bar.abandon();
+
+                                return Err(error);
+                            }
+                        };
+
+                    batch_loss += weight * loss;
+
+                    batch_predictions.push(prediction);
+                }
 
-                for (input_data, target_data) in input_batch.iter().zip(target_batch.iter()) {
-                    let prediction = self.evaluate(input_data);
+                if let Some(error) = self.check_divergence(batch_loss, epoch, i) {
+                    match non_finite_action {
+                        NonFiniteAction::Abort => {
+                            progress_bar.abandon();
 
-                    batch_loss += (self.loss)(target_data, &prediction);
+                            return Err(error.into());
+                        }
+                        NonFiniteAction::SkipBatch => {
+                            self.layers
+                                .iter_mut()
+                                .for_each(|layer| layer.clear_error_and_delta());
 
-                    self.backpropagation(target_data, input_data, &prediction);
+                            skipped_batches += 1;
 
-                    epoch_predictions.push(prediction);
+                            continue;
+                        }
+                    }
+                }
+
+                let gradient_norm = global_gradient_norm(&self.layers, batch.len());
+
+                if let Some(clipping) = gradient_clipping {
+                    clip_gradients(&mut self.layers, batch.len(), clipping, gradient_norm);
                 }
 
                 self.layers.iter_mut().for_each(|layer| {
-                    optimizer.update_params(input_batch.len(), layer, learning_rate);
+                    optimizer.update_params(batch.len(), layer, epoch_learning_rate);
                     layer.clear_error_and_delta()
                 });
 
-                epoch_loss += batch_loss / input_batch.len() as f32;
-                progress_bar.set_message(format!("Loss: {:.4}", epoch_loss / (i + 1) as f32));
+                epoch_loss += batch_loss / T::cast(batch.len() as f64);
+
+                // Only the predictions of the trained batches count in the metrics
+                for (prediction, target) in batch_predictions.iter().zip(batch.targets.iter()) {
+                    for metric in metrics.iter_mut() {
+                        metric.update(prediction, target)?;
+                    }
+                }
+
+                trained_samples += batch.len();
+
+                progress_bar.set_message(format!(
+                    "Loss: {:.4} Grad norm: {:.4}",
+                    epoch_loss / T::cast((i + 1 - skipped_batches) as f64),
+                    gradient_norm
+                ));
             }
 
             progress_bar.finish();
 
-            print!("({}) Loss: {:.4} ", epoch, epoch_loss / batches_ammount as f32);
+            let trained_batches = batches_ammount - skipped_batches;
 
-            print_metrics(epoch_predictions, &metrics, &y);
+            // Without a trained batch there is no loss to average
+            if trained_batches == 0 && skipped_batches > 0 {
+                print!(
+                    "({}) No loss, all {} batches were skipped as non-finite ",
+                    epoch, skipped_batches
+                );
+            } else if trained_batches == 0 {
+                print!("({}) No batch to train on ", epoch);
+            } else {
+                print!(
+                    "({}) Loss: {:.4} ",
+                    epoch,
+                    epoch_loss / T::cast(trained_batches as f64)
+                );
+
+                if skipped_batches > 0 {
+                    print!("(skipped {} non-finite batches) ", skipped_batches);
+                }
+            }
+
+            if trained_samples > 0 {
+                print_metrics(&metrics);
+            }
             println!()
         }
+
+        Ok(())
     }
 
-    fn backpropagation(
+    // Adds the gradients of the sample, returning its unweighted loss and the prediction
+    fn train_sample(
         &mut self,
-        expected: &DMatrix<f32>,
-        network_input: &DMatrix<f32>,
-        predicted: &DMatrix<f32>,
-    ) {
-        let mut next_layer_delta = (self.loss_derivative)(&expected, &predicted);
+        input: &DMatrix<T>,
+        target: &DMatrix<T>,
+        weight: T,
+    ) -> Result<(T, DMatrix<T>), NeuraError> {
+        let input = self.preprocess(input)?;
+        let prediction = self.forward(&input)?;
+        let loss = self.loss.evaluate(target, &prediction)?;
+
+        self.backpropagation(target, &input, &prediction, weight)?;
+
+        Ok((loss, prediction))
+    }
+
+    fn check_divergence(
+        &self,
+        batch_loss: T,
+        epoch: usize,
+        batch: usize,
+    ) -> Option<DivergenceError> {
+        if !batch_loss.is_finite() {
+            return Some(DivergenceError::NonFiniteLoss { epoch, batch });
+        }
+
+        self.layers
+            .iter()
+            .position(|layer| layer.has_non_finite_gradients())
+            .map(|layer| DivergenceError::NonFiniteGradients {
+                epoch,
+                batch,
+                layer,
+            })
+    }
+
+    // The gradients of the sample are scaled by its weight
+    pub(crate) fn backpropagation(
+        &mut self,
+        expected: &DMatrix<T>,
+        network_input: &DMatrix<T>,
+        predicted: &DMatrix<T>,
+        weight: T,
+    ) -> Result<(), NeuraError> {
+        if let Loss::Tape { .. } = self.loss {
+            return self.backpropagation_on_tape(expected, network_input, weight);
+        }
+
+        let mut next_layer_delta = self.loss.derivative(expected, predicted)? * weight;
 
         let zeros = DMatrix::zeros(0, 0);
 
@@ -131,39 +388,246 @@ impl Model {
             (next_layer_errors, next_layer_delta) = self.layers[i].propagate_error(
                 last_layer,
                 &next_layer_delta,
-                &next_layer_weights,
-                &previous_layer_output,
-            );
+                next_layer_weights,
+                previous_layer_output,
+            )?;
 
             self.layers[i].sum_errors_and_deltas(&next_layer_delta, &next_layer_errors)
         }
+
+        Ok(())
     }
 
-    pub fn evaluate(&mut self, data: &DMatrix<f32>) -> DMatrix<f32> {
-        let mut last_output = data;
+    fn backpropagation_on_tape(
+        &mut self,
+        expected: &DMatrix<T>,
+        network_input: &DMatrix<T>,
+        weight: T,
+    ) -> Result<(), NeuraError> {
+        let tape = Tape::new();
+
+        let mut output = tape.variable(network_input.clone());
+        let mut params = Vec::with_capacity(self.layers.len());
+
+        for layer in self.layers.iter() {
+            let weights = tape.variable(layer.get_weights_reference().clone());
+            let biases = tape.variable(layer.get_biases_reference().clone());
+
+            output = layer
+                .get_activation()
+                .trace(&weights.matmul(&output).add(&biases));
+
+            params.push((weights, biases));
+        }
+
+        let loss = self.loss.trace(expected, &output)?.scale(weight);
+        let gradients = tape.gradients(&loss)?;
 
         self.layers
             .iter_mut()
-            .for_each(|layer| last_output = layer.forward(&last_output));
+            .zip(params.iter())
+            .for_each(|(layer, (weights, biases))| {
+                layer.sum_errors_and_deltas(&gradients.wrt(biases), &gradients.wrt(weights))
+            });
+
+        Ok(())
+    }
+
+    pub fn calculate_loss(
+        &self,
+        expected: &DMatrix<T>,
+        predicted: &DMatrix<T>,
+    ) -> Result<T, NeuraError> {
+        self.loss.evaluate(expected, predicted)
+    }
+
+    pub fn get_loss(&self) -> Loss<T> {
+        self.loss
+    }
+
+    pub fn get_layers_reference(&self) -> &[Layer<T>] {
+        &self.layers
+    }
+
+    pub fn get_layers_mut_reference(&mut self) -> &mut [Layer<T>] {
+        &mut self.layers
+    }
+
+    pub fn set_pipeline(&mut self, pipeline: Option<Pipeline>) {
+        self.pipeline = pipeline;
+    }
+
+    pub fn get_pipeline_reference(&self) -> Option<&Pipeline> {
+        self.pipeline.as_ref()
+    }
+
+    // Weight of each class in the loss, indexed by the class of the targets
+    pub fn set_class_weights(&mut self, class_weights: Option<Vec<T>>) {
+        self.class_weights = class_weights;
+    }
+
+    pub fn get_class_weights_reference(&self) -> Option<&Vec<T>> {
+        self.class_weights.as_ref()
+    }
+
+    fn class_weight(&self, target: &DMatrix<T>) -> T {
+        match &self.class_weights {
+            Some(weights) => weights
+                .get(target_class(target))
+                .copied()
+                .unwrap_or(T::one()),
+            None => T::one(),
+        }
+    }
+
+    fn preprocess<'a>(&self, data: &'a DMatrix<T>) -> Result<Cow<'a, DMatrix<T>>, NeuraError> {
+        Ok(match &self.pipeline {
+            Some(pipeline) => Cow::Owned(pipeline.transform(data)?),
+            None => Cow::Borrowed(data),
+        })
+    }
+
+    // Applies the pipeline, if any, to the raw input before the layers. Inputs
+    // of the wrong shape are errors.
+    pub fn evaluate(&self, data: &DMatrix<T>) -> Result<DMatrix<T>, NeuraError> {
+        self.evaluate_with(data, &mut Workspace::new())
+    }
+
+    // Evaluates without touching the layers, keeping their outputs in the
+    // workspace instead, so that threads can share the model
+    pub fn evaluate_with(
+        &self,
+        data: &DMatrix<T>,
+        workspace: &mut Workspace<T>,
+    ) -> Result<DMatrix<T>, NeuraError> {
+        let data = self.preprocess(data)?;
+
+        workspace.resize(self.layers.len());
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let input = match index {
+                0 => data.as_ref(),
+                _ => &workspace.activated_outputs[index - 1],
+            };
+
+            let output = layer.infer(input, &mut workspace.raw_outputs[index])?;
+
+            workspace.activated_outputs[index] = output;
+        }
+
+        Ok(workspace
+            .activated_outputs
+            .last()
+            .cloned()
+            .unwrap_or_else(|| data.into_owned()))
+    }
 
-        last_output.clone()
+    // Runs the layers on an already preprocessed input
+    pub(crate) fn forward(&mut self, data: &DMatrix<T>) -> Result<DMatrix<T>, NeuraError> {
+        let mut last_output = data;
+
+        for layer in self.layers.iter_mut() {
+            last_output = layer.forward(last_output)?;
+        }
+
+        Ok(last_output.clone())
     }
 
-    pub fn test(&mut self, metrics: Vec<String>, x: &Vec<DMatrix<f32>>, y: &Vec<DMatrix<f32>>) {
-        let mut loss = 0_f32;
-        let mut predictions = Vec::with_capacity(x.len());
+    // Evaluates many inputs with one matrix product per layer, failing on the
+    // first input of the wrong shape
+    pub fn evaluate_batch(&self, inputs: &[DMatrix<T>]) -> Result<Vec<DMatrix<T>>, NeuraError> {
+        let input_dim = match self.layers.first() {
+            Some(layer) => layer.get_input_dim(),
+            None => {
+                return inputs
+                    .iter()
+                    .map(|input| Ok(self.preprocess(input)?.into_owned()))
+                    .collect()
+            }
+        };
 
-        for (_x, _y) in x.iter().zip(y.iter()) {
-            let prediction = self.evaluate(_x);
+        let mut values = Vec::with_capacity(input_dim * inputs.len());
+
+        for (index, input) in inputs.iter().enumerate() {
+            let input = self.preprocess(input)?;
+
+            check_shape(&format!("input {}", index), (input_dim, 1), &input)?;
+
+            values.extend(input.iter().copied());
+        }
 
-            loss += (self.loss)(_y, &prediction);
+        let mut outputs = DMatrix::from_vec(input_dim, inputs.len(), values);
 
-            predictions.push(prediction);
+        for layer in self.layers.iter() {
+            outputs = layer.infer_batch(&outputs)?;
         }
 
-        print!("Loss: {:.4} ", loss / x.len() as f32);
+        Ok((0..outputs.ncols())
+            .map(|index| outputs.columns(index, 1).into_owned())
+            .collect())
+    }
+
+    // Rows of the actual classes of the dataset and columns of the predicted ones,
+    // for classification_report
+    pub fn confusion_matrix(&self, dataset: &dyn Dataset<T>) -> Result<DMatrix<usize>, NeuraError> {
+        let mut confusion_matrix = DMatrix::zeros(0, 0);
+        let mut workspace = Workspace::new();
+
+        for batch in dataset.batches(64, false) {
+            let batch = batch?;
+
+            for (input, target) in batch.inputs.iter().zip(batch.targets.iter()) {
+                let prediction = self.evaluate_with(input, &mut workspace)?;
+
+                check_shape("prediction", target.shape(), &prediction)?;
+
+                add_to_confusion_matrix(&mut confusion_matrix, &prediction, target)?;
+            }
+        }
+
+        Ok(confusion_matrix)
+    }
+
+    // Prints and returns the loss followed by the metrics
+    pub fn test(
+        &self,
+        mut metrics: Vec<Box<dyn Metric<T>>>,
+        dataset: &dyn Dataset<T>,
+    ) -> Result<Vec<(String, f32)>, NeuraError> {
+        let mut loss = T::zero();
+        let mut workspace = Workspace::new();
+
+        metrics.iter_mut().for_each(|metric| metric.reset());
+
+        for batch in dataset.batches(64, false) {
+            let batch = batch?;
+
+            for (_x, _y) in batch.inputs.iter().zip(batch.targets.iter()) {
+                let prediction = self.evaluate_with(_x, &mut workspace)?;
+
+                loss += self.loss.evaluate(_y, &prediction)?;
+
+                for metric in metrics.iter_mut() {
+                    metric.update(&prediction, _y)?;
+                }
+            }
+        }
+
+        let loss = loss / T::cast(dataset.len() as f64);
+
+        print!("Loss: {:.4} ", loss);
+
+        let mut scores = vec![("loss".to_string(), loss.as_f64() as f32)];
+
+        scores.extend(
+            metrics
+                .iter()
+                .map(|metric| (metric.name(), metric.result())),
+        );
+
+        print_metrics(&metrics);
+        println!();
 
-        print_metrics(predictions, &metrics, &y);
-        println!()
+        Ok(scores)
     }
 }
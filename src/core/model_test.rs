@@ -1,10 +1,27 @@
 #[cfg(test)]
 mod tests {
+    use std::{sync::Arc, thread};
+
     use nalgebra::{DMatrix, DVector};
 
     use crate::{
-        core::{layer::Layer, model::Model},
-        functions::losses::{mse, mse_derivative},
+        autograd::tape::Var,
+        core::{
+            divergence::{DivergenceError, NonFiniteAction},
+            layer::Layer,
+            model::{FitOptions, Model},
+            workspace::Workspace,
+        },
+        data::{
+            augmentation::{Augmentation, Augmenter},
+            dataset::{Dataset, InMemoryDataset},
+        },
+        error::NeuraError,
+        functions::{
+            activations::{sigmoid, sigmoid_derivative, softmax, softmax_derivative},
+            losses::{binary_crossentropy, binary_crossentropy_derivative, mse, mse_derivative},
+        },
+        optimizers::{rmsprop::RMSProp, schedule::LearningRateSchedule},
     };
 
     #[test]
@@ -23,10 +40,339 @@ mod tests {
             DMatrix::from_vec(1, 3, vec![0.0, 0.0, 0.0]),
         );
 
-        let mut model = Model::new(vec![hidden_layer, output_layer], mse, mse_derivative);
+        let model = Model::new(vec![hidden_layer, output_layer], mse, mse_derivative);
 
         let data = DMatrix::from_vec(2, 1, vec![0.0, 1.0]);
 
-        assert_eq!(model.evaluate(&data), DVector::from_vec(vec![1.0]));
+        assert_eq!(model.evaluate(&data).unwrap(), DVector::from_vec(vec![1.0]));
+    }
+
+    fn fit_single_sample(
+        model: &mut Model,
+        non_finite_action: NonFiniteAction,
+    ) -> Result<(), DivergenceError> {
+        model
+            .fit(
+                FitOptions {
+                    batch_size: 1,
+                    epochs: 1,
+                    non_finite_action,
+                    ..FitOptions::default()
+                },
+                &mut RMSProp::new(0.9),
+                &InMemoryDataset::new(
+                    vec![DMatrix::from_vec(2, 1, vec![0.0, 1.0])],
+                    vec![DMatrix::from_vec(1, 1, vec![1.0])],
+                )
+                .unwrap(),
+            )
+            .map_err(|error| match error {
+                NeuraError::Divergence(error) => error,
+                error => panic!("Unexpected error {}", error),
+            })
+    }
+
+    #[test]
+    fn test_fit_stops_on_non_finite_loss() {
+        let layer = Layer::from(
+            |x| x.clone(),
+            |x| x.map(|_| 1.0),
+            DMatrix::from_vec(1, 1, vec![0.0]),
+            DMatrix::from_vec(1, 2, vec![0.0, f32::NAN]),
+        );
+
+        let mut model = Model::new(vec![layer], mse, mse_derivative);
+
+        assert_eq!(
+            Err(DivergenceError::NonFiniteLoss { epoch: 0, batch: 0 }),
+            fit_single_sample(&mut model, NonFiniteAction::Abort)
+        );
+
+        assert_eq!(
+            Ok(()),
+            fit_single_sample(&mut model, NonFiniteAction::SkipBatch)
+        );
+    }
+
+    #[test]
+    fn test_fit_stops_on_non_finite_gradients() {
+        // The clamped loss stays finite while its derivative divides by zero
+        let layer = Layer::from(
+            |x| x.clone(),
+            |x| x.map(|_| 1.0),
+            DMatrix::from_vec(1, 1, vec![0.0]),
+            DMatrix::from_vec(1, 2, vec![0.0, 0.0]),
+        );
+
+        let mut model = Model::new(
+            vec![layer],
+            binary_crossentropy,
+            binary_crossentropy_derivative,
+        );
+
+        assert_eq!(
+            Err(DivergenceError::NonFiniteGradients {
+                epoch: 0,
+                batch: 0,
+                layer: 0
+            }),
+            fit_single_sample(&mut model, NonFiniteAction::Abort)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_rejects_wrong_input_shape() {
+        let layer = Layer::from(
+            |x| x.clone(),
+            |x| x.map(|_| 1.0),
+            DMatrix::from_vec(1, 1, vec![0.0]),
+            DMatrix::from_vec(1, 2, vec![1.0, 1.0]),
+        );
+        let model = Model::new(vec![layer], mse, mse_derivative);
+
+        let result = model.evaluate(&DMatrix::from_vec(3, 1, vec![0.0, 1.0, 2.0]));
+
+        assert!(matches!(
+            result,
+            Err(NeuraError::ShapeMismatch {
+                expected: (2, 1),
+                found: (3, 1),
+                ..
+            })
+        ));
+        assert_eq!(
+            "The layer input should be 2x1 but is 3x1",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_shared_model_evaluates_concurrently() {
+        let mut model = Model::new(
+            vec![
+                Layer::new(sigmoid, sigmoid_derivative, 2, 4),
+                Layer::new(sigmoid, sigmoid_derivative, 4, 1),
+            ],
+            mse,
+            mse_derivative,
+        );
+
+        let inputs: Vec<DMatrix<f32>> = (0..8)
+            .map(|index| DMatrix::from_vec(2, 1, vec![index as f32, 1.0]))
+            .collect();
+        let expected: Vec<DMatrix<f32>> = inputs
+            .iter()
+            .map(|input| model.forward(input).unwrap())
+            .collect();
+
+        let model = Arc::new(model);
+
+        let handles: Vec<_> = inputs
+            .into_iter()
+            .map(|input| {
+                let model = Arc::clone(&model);
+
+                thread::spawn(move || {
+                    let mut workspace = Workspace::new();
+                    let prediction = model.evaluate_with(&input, &mut workspace).unwrap();
+
+                    assert_eq!(2, workspace.get_outputs_reference().len());
+
+                    prediction
+                })
+            })
+            .collect();
+
+        // The workspace adds the biases in the product, rounding differently
+        for (handle, expected) in handles.into_iter().zip(expected) {
+            assert!((expected - handle.join().unwrap()).abs().max() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_batch_matches_evaluate() {
+        let model = Model::new(
+            vec![
+                Layer::new(sigmoid, sigmoid_derivative, 2, 4),
+                Layer::new(softmax, softmax_derivative, 4, 3),
+            ],
+            mse,
+            mse_derivative,
+        );
+
+        let inputs: Vec<DMatrix<f32>> = (0..5)
+            .map(|index| DMatrix::from_vec(2, 1, vec![index as f32, -1.0]))
+            .collect();
+
+        let predictions = model.evaluate_batch(&inputs).unwrap();
+
+        assert_eq!(inputs.len(), predictions.len());
+
+        for (input, prediction) in inputs.iter().zip(predictions.iter()) {
+            let expected = model.evaluate(input).unwrap();
+
+            assert!((expected - prediction).abs().max() < 1e-6);
+        }
+
+        assert!(model.evaluate_batch(&[]).unwrap().is_empty());
+
+        let mut inputs = inputs;
+        inputs.push(DMatrix::zeros(3, 1));
+
+        assert_eq!(
+            "The input 5 should be 2x1 but is 3x1",
+            model.evaluate_batch(&inputs).unwrap_err().to_string()
+        );
+    }
+
+    // Fails to read its last sample
+    struct UnreadableDataset;
+
+    impl Dataset for UnreadableDataset {
+        fn len(&self) -> usize {
+            2
+        }
+
+        fn get(&self, index: usize) -> Result<(DMatrix<f32>, DMatrix<f32>), NeuraError> {
+            match index {
+                0 => Ok((DMatrix::zeros(2, 1), DMatrix::zeros(1, 1))),
+                _ => Err(NeuraError::InvalidArgument("Unreadable sample".to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_dataset_errors_are_returned() {
+        let mut model = Model::new(
+            vec![Layer::new(sigmoid, sigmoid_derivative, 2, 1)],
+            mse,
+            mse_derivative,
+        );
+
+        let result = model.fit(
+            FitOptions::default(),
+            &mut RMSProp::new(0.9),
+            &UnreadableDataset,
+        );
+
+        assert!(
+            matches!(result, Err(NeuraError::InvalidArgument(message)) if message == "Unreadable sample")
+        );
+        assert!(model.test(Vec::new(), &UnreadableDataset).is_err());
+        assert!(model.confusion_matrix(&UnreadableDataset).is_err());
+    }
+
+    #[test]
+    fn test_fit_rejects_inputs_the_augmenter_can_not_handle() {
+        let mut model = Model::new(
+            vec![Layer::new(sigmoid, sigmoid_derivative, 2, 1)],
+            mse,
+            mse_derivative,
+        );
+        let mut augmenter = Augmenter::new(3, 3, vec![Augmentation::Shift(1.0)], 0).unwrap();
+
+        let result = model.fit(
+            FitOptions {
+                augmenter: Some(&mut augmenter),
+                ..FitOptions::default()
+            },
+            &mut RMSProp::new(0.9),
+            &InMemoryDataset::new(vec![DMatrix::zeros(2, 1)], vec![DMatrix::zeros(1, 1)]).unwrap(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(NeuraError::ShapeMismatch {
+                expected: (9, 1),
+                found: (2, 1),
+                ..
+            })
+        ));
+    }
+
+    // Squared errors of every output instead of their sum
+    fn unreduced_loss(expected: &Var, predicted: &Var) -> Var {
+        predicted.sub(expected).powi(2)
+    }
+
+    #[test]
+    fn test_tape_loss_must_be_a_scalar() {
+        let mut model = Model::new_on_tape(
+            vec![Layer::new(sigmoid, sigmoid_derivative, 2, 2)],
+            unreduced_loss,
+        );
+        let input = DMatrix::from_vec(2, 1, vec![0.0, 1.0]);
+        let expected = DMatrix::from_vec(2, 1, vec![1.0, 0.0]);
+
+        assert!(matches!(
+            model.calculate_loss(&expected, &input),
+            Err(NeuraError::ShapeMismatch {
+                expected: (1, 1),
+                found: (2, 1),
+                ..
+            })
+        ));
+
+        let result = model.fit(
+            FitOptions {
+                batch_size: 1,
+                epochs: 1,
+                ..FitOptions::default()
+            },
+            &mut RMSProp::new(0.9),
+            &InMemoryDataset::new(vec![input], vec![expected]).unwrap(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(NeuraError::ShapeMismatch {
+                expected: (1, 1),
+                found: (2, 1),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_fit_rejects_an_empty_batch_size() {
+        let mut model = Model::new(
+            vec![Layer::new(sigmoid, sigmoid_derivative, 2, 1)],
+            mse,
+            mse_derivative,
+        );
+
+        let result = model.fit(
+            FitOptions {
+                batch_size: 0,
+                ..FitOptions::default()
+            },
+            &mut RMSProp::new(0.9),
+            &InMemoryDataset::new(vec![DMatrix::zeros(2, 1)], vec![DMatrix::zeros(1, 1)]).unwrap(),
+        );
+
+        assert!(matches!(result, Err(NeuraError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_fit_rejects_an_invalid_schedule() {
+        let mut model = Model::new(
+            vec![Layer::new(sigmoid, sigmoid_derivative, 2, 1)],
+            mse,
+            mse_derivative,
+        );
+
+        let result = model.fit(
+            FitOptions {
+                schedule: LearningRateSchedule::Step {
+                    every: 0,
+                    factor: 0.5,
+                },
+                ..FitOptions::default()
+            },
+            &mut RMSProp::new(0.9),
+            &InMemoryDataset::new(vec![DMatrix::zeros(2, 1)], vec![DMatrix::zeros(1, 1)]).unwrap(),
+        );
+
+        assert!(matches!(result, Err(NeuraError::InvalidArgument(_))));
     }
 }
@@ -0,0 +1,164 @@
+use std::mem::size_of;
+
+use half::{bf16, f16};
+use nalgebra::DMatrix;
+
+use crate::{
+    error::{check_shape, NeuraError},
+    preprocessing::pipeline::Pipeline,
+};
+
+use super::{activation::Activation, layer::Layer, loss::Loss, model::Model};
+
+// Storage types for the parameters of a model. The values are converted back
+// to f32 when used, so every computation still accumulates in f32.
+pub trait ReducedPrecision: Copy + Send + Sync + 'static {
+    fn from_f32(value: f32) -> Self;
+
+    fn to_f32(self) -> f32;
+}
+
+impl ReducedPrecision for f16 {
+    fn from_f32(value: f32) -> Self {
+        f16::from_f32(value)
+    }
+
+    fn to_f32(self) -> f32 {
+        f16::to_f32(self)
+    }
+}
+
+impl ReducedPrecision for bf16 {
+    fn from_f32(value: f32) -> Self {
+        bf16::from_f32(value)
+    }
+
+    fn to_f32(self) -> f32 {
+        bf16::to_f32(self)
+    }
+}
+
+// Column-major, like nalgebra's DMatrix
+#[derive(Clone)]
+pub struct ReducedPrecisionMatrix<S: ReducedPrecision> {
+    data: Vec<S>,
+    nrows: usize,
+    ncols: usize,
+}
+
+impl<S: ReducedPrecision> ReducedPrecisionMatrix<S> {
+    pub fn from_matrix(matrix: &DMatrix<f32>) -> Self {
+        Self {
+            data: matrix.iter().map(|&x| S::from_f32(x)).collect(),
+            nrows: matrix.nrows(),
+            ncols: matrix.ncols(),
+        }
+    }
+
+    pub fn to_matrix(&self) -> DMatrix<f32> {
+        DMatrix::from_iterator(self.nrows, self.ncols, self.data.iter().map(|x| x.to_f32()))
+    }
+
+    // self * other, converting one stored value at a time instead of the whole matrix
+    pub fn mul_matrix(&self, other: &DMatrix<f32>) -> Result<DMatrix<f32>, NeuraError> {
+        check_shape("right operand", (self.ncols, other.ncols()), other)?;
+
+        let mut result = DMatrix::zeros(self.nrows, other.ncols());
+
+        for k in 0..other.ncols() {
+            for (column, stored_column) in self.data.chunks(self.nrows.max(1)).enumerate() {
+                let factor = other[(column, k)];
+
+                for (row, value) in stored_column.iter().enumerate() {
+                    result[(row, k)] += value.to_f32() * factor;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.nrows, self.ncols)
+    }
+
+    pub fn get_memory_size(&self) -> usize {
+        self.data.len() * size_of::<S>()
+    }
+}
+
+pub struct ReducedPrecisionLayer<S: ReducedPrecision> {
+    activation: Activation<f32>,
+    biases: ReducedPrecisionMatrix<S>,
+    weights: ReducedPrecisionMatrix<S>,
+}
+
+// Inference copy of a Model<f32> holding its parameters as f16 or bf16
+pub struct ReducedPrecisionModel<S: ReducedPrecision> {
+    layers: Vec<ReducedPrecisionLayer<S>>,
+    loss: Loss<f32>,
+    pipeline: Option<Pipeline>,
+}
+
+impl<S: ReducedPrecision> ReducedPrecisionModel<S> {
+    pub fn from_model(model: &Model<f32>) -> Self {
+        let layers = model
+            .get_layers_reference()
+            .iter()
+            .map(|layer| ReducedPrecisionLayer {
+                activation: layer.get_activation(),
+                biases: ReducedPrecisionMatrix::from_matrix(layer.get_biases_reference()),
+                weights: ReducedPrecisionMatrix::from_matrix(layer.get_weights_reference()),
+            })
+            .collect();
+
+        Self {
+            layers,
+            loss: model.get_loss(),
+            pipeline: model.get_pipeline_reference().cloned(),
+        }
+    }
+
+    // Restores a Model<f32>, e.g. to keep training
+    pub fn to_model(&self) -> Model<f32> {
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| {
+                Layer::with_params(
+                    layer.activation,
+                    layer.biases.to_matrix(),
+                    layer.weights.to_matrix(),
+                )
+            })
+            .collect();
+
+        let mut model = Model::with_loss(layers, self.loss);
+
+        model.set_pipeline(self.pipeline.clone());
+
+        model
+    }
+
+    pub fn evaluate(&self, data: &DMatrix<f32>) -> Result<DMatrix<f32>, NeuraError> {
+        let data = match &self.pipeline {
+            Some(pipeline) => pipeline.transform(data)?,
+            None => data.clone(),
+        };
+
+        self.layers.iter().try_fold(data, |output, layer| {
+            check_shape("layer input", (layer.weights.ncols, 1), &output)?;
+
+            let raw_output = layer.weights.mul_matrix(&output)? + layer.biases.to_matrix();
+
+            layer.activation.forward(&raw_output)
+        })
+    }
+
+    pub fn get_memory_size(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| layer.weights.get_memory_size() + layer.biases.get_memory_size())
+            .sum()
+    }
+}
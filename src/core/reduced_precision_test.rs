@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use half::{bf16, f16};
+    use nalgebra::DMatrix;
+
+    use crate::{
+        core::{
+            layer::Layer,
+            model::Model,
+            reduced_precision::{ReducedPrecisionMatrix, ReducedPrecisionModel},
+        },
+        functions::{
+            activations::{relu, relu_derivative, softmax, softmax_derivative},
+            losses::{categorical_crossentropy, categorical_crossentropy_derivative},
+        },
+    };
+
+    fn model() -> Model<f32> {
+        let hidden_layer = Layer::from(
+            relu,
+            relu_derivative,
+            DMatrix::from_vec(3, 1, vec![0.1, 0.2, 0.3]),
+            DMatrix::from_vec(3, 2, vec![0.5, -0.1, 0.7, 0.25, 0.1, -0.7]),
+        );
+
+        let output_layer = Layer::from(
+            softmax,
+            softmax_derivative,
+            DMatrix::from_vec(2, 1, vec![0.0, 0.1]),
+            DMatrix::from_vec(2, 3, vec![0.3, -0.2, 0.6, 0.4, -0.5, 0.9]),
+        );
+
+        Model::new(
+            vec![hidden_layer, output_layer],
+            categorical_crossentropy,
+            categorical_crossentropy_derivative,
+        )
+    }
+
+    #[test]
+    fn test_mul_matrix() {
+        let matrix = DMatrix::from_row_slice(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let other = DMatrix::from_row_slice(3, 2, &[1.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+
+        let stored = ReducedPrecisionMatrix::<f16>::from_matrix(&matrix);
+
+        assert_eq!(&matrix * &other, stored.mul_matrix(&other).unwrap());
+        assert_eq!(matrix, stored.to_matrix());
+    }
+
+    #[test]
+    fn test_evaluate_in_reduced_precision() {
+        let model = model();
+        let data = DMatrix::from_vec(2, 1, vec![0.8, 0.3]);
+
+        let expected = model.evaluate(&data).unwrap();
+
+        let half_model = ReducedPrecisionModel::<f16>::from_model(&model);
+        let bfloat_model = ReducedPrecisionModel::<bf16>::from_model(&model);
+
+        assert!((half_model.evaluate(&data).unwrap() - &expected).amax() < 1e-3);
+        assert!((bfloat_model.evaluate(&data).unwrap() - &expected).amax() < 1e-2);
+
+        // 17 parameters of 2 bytes each
+        assert_eq!(34, half_model.get_memory_size());
+        assert_eq!(34, bfloat_model.get_memory_size());
+
+        let restored = half_model.to_model();
+
+        assert!((restored.evaluate(&data).unwrap() - &expected).amax() < 1e-3);
+    }
+}
@@ -0,0 +1,149 @@
+use std::fs;
+
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::NeuraError,
+    functions::registry::{activation_by_name, loss_by_name},
+    preprocessing::pipeline::Pipeline,
+};
+
+use super::{float::Float, layer::Layer, model::Model};
+
+// Column-major values, as nalgebra stores them
+#[derive(Serialize, Deserialize)]
+struct SavedMatrix {
+    rows: usize,
+    columns: usize,
+    data: Vec<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedLayer {
+    activation: String,
+    biases: SavedMatrix,
+    weights: SavedMatrix,
+}
+
+// JSON file of a model. Activations and losses are saved by their registry
+// name, so only the models built with the registry functions can be saved.
+#[derive(Serialize, Deserialize)]
+struct SavedModel {
+    layers: Vec<SavedLayer>,
+    loss: String,
+    pipeline: Option<Pipeline>,
+}
+
+impl SavedMatrix {
+    fn from_matrix<T: Float>(matrix: &DMatrix<T>) -> Self {
+        Self {
+            rows: matrix.nrows(),
+            columns: matrix.ncols(),
+            data: matrix.iter().map(|value| value.as_f64()).collect(),
+        }
+    }
+
+    fn to_matrix<T: Float>(&self) -> Result<DMatrix<T>, NeuraError> {
+        if self.rows * self.columns != self.data.len() {
+            return Err(NeuraError::Format(format!(
+                "A {}x{} matrix can't hold {} values",
+                self.rows,
+                self.columns,
+                self.data.len()
+            )));
+        }
+
+        Ok(DMatrix::from_iterator(
+            self.rows,
+            self.columns,
+            self.data.iter().map(|&value| T::cast(value)),
+        ))
+    }
+}
+
+impl<T: Float> Model<T> {
+    pub fn to_json(&self) -> Result<String, NeuraError> {
+        let layers = self
+            .get_layers_reference()
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                let activation = layer.get_activation().name().ok_or_else(|| {
+                    NeuraError::Format(format!(
+                        "The activation of the layer {} is not registered",
+                        i
+                    ))
+                })?;
+
+                Ok(SavedLayer {
+                    activation: activation.to_string(),
+                    biases: SavedMatrix::from_matrix(layer.get_biases_reference()),
+                    weights: SavedMatrix::from_matrix(layer.get_weights_reference()),
+                })
+            })
+            .collect::<Result<Vec<SavedLayer>, NeuraError>>()?;
+
+        let loss = self
+            .get_loss()
+            .name()
+            .ok_or_else(|| NeuraError::Format("The loss is not registered".to_string()))?;
+
+        Ok(serde_json::to_string(&SavedModel {
+            layers,
+            loss: loss.to_string(),
+            pipeline: self.get_pipeline_reference().cloned(),
+        })?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, NeuraError> {
+        let saved: SavedModel = serde_json::from_str(json)?;
+
+        let layers = saved
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                let activation = activation_by_name(&layer.activation).ok_or_else(|| {
+                    NeuraError::Format(format!(
+                        "Unknown activation '{}' in the layer {}",
+                        layer.activation, i
+                    ))
+                })?;
+
+                let biases = layer.biases.to_matrix()?;
+                let weights = layer.weights.to_matrix()?;
+
+                if biases.shape() != (weights.nrows(), 1) {
+                    return Err(NeuraError::Format(format!(
+                        "The layer {} has {:?} biases for {:?} weights",
+                        i,
+                        biases.shape(),
+                        weights.shape()
+                    )));
+                }
+
+                Ok(Layer::with_params(activation, biases, weights))
+            })
+            .collect::<Result<Vec<Layer<T>>, NeuraError>>()?;
+
+        let loss = loss_by_name(&saved.loss)
+            .ok_or_else(|| NeuraError::Format(format!("Unknown loss '{}'", saved.loss)))?;
+
+        let mut model = Model::with_loss(layers, loss);
+
+        model.set_pipeline(saved.pipeline);
+
+        Ok(model)
+    }
+
+    pub fn save(&self, file_path: &str) -> Result<(), NeuraError> {
+        fs::write(file_path, self.to_json()?)?;
+
+        Ok(())
+    }
+
+    pub fn load(file_path: &str) -> Result<Self, NeuraError> {
+        Self::from_json(&fs::read_to_string(file_path)?)
+    }
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use nalgebra::DMatrix;
+
+    use crate::{
+        core::{layer::Layer, model::Model},
+        data::dataset::InMemoryDataset,
+        error::NeuraError,
+        functions::{
+            activations::{relu, relu_derivative},
+            losses::{mse, mse_derivative},
+            registry::{activation_by_name, loss_by_name},
+        },
+        preprocessing::{
+            encoders::LabelEncoder,
+            pipeline::{Pipeline, Transformer},
+            scalers::StandardScaler,
+        },
+    };
+
+    fn model() -> Model {
+        let hidden_layer = Layer::with_params(
+            activation_by_name("relu").unwrap(),
+            DMatrix::from_vec(2, 1, vec![0.1, -0.2]),
+            DMatrix::from_vec(2, 2, vec![0.5, -0.3, 0.8, 0.2]),
+        );
+
+        let output_layer = Layer::with_params(
+            activation_by_name("softmax_on_tape").unwrap(),
+            DMatrix::from_vec(2, 1, vec![0.0, 0.3]),
+            DMatrix::from_vec(2, 2, vec![1.0, -1.0, 0.5, 0.25]),
+        );
+
+        Model::with_loss(
+            vec![hidden_layer, output_layer],
+            loss_by_name("mse").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_saved_model_predicts_the_same() {
+        let mut model = model();
+
+        let mut pipeline = Pipeline::with_labels(
+            vec![Transformer::StandardScaler(StandardScaler::new())],
+            LabelEncoder::from_classes(vec!["cat".to_string(), "dog".to_string()]),
+        );
+
+        pipeline
+            .fit(
+                &InMemoryDataset::new(
+                    vec![
+                        DMatrix::from_vec(2, 1, vec![0.0, 10.0]),
+                        DMatrix::from_vec(2, 1, vec![2.0, 30.0]),
+                    ],
+                    vec![DMatrix::zeros(2, 1); 2],
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        model.set_pipeline(Some(pipeline));
+
+        let path = env::temp_dir().join("neura_rust_model.json");
+        let path = path.to_str().unwrap();
+
+        model.save(path).unwrap();
+
+        let loaded = Model::<f32>::load(path).unwrap();
+
+        let data = DMatrix::from_vec(2, 1, vec![2.0, 10.0]);
+
+        assert_eq!(
+            model.evaluate(&data).unwrap(),
+            loaded.evaluate(&data).unwrap()
+        );
+        assert_eq!(
+            model.get_pipeline_reference(),
+            loaded.get_pipeline_reference()
+        );
+
+        // The pipeline is applied, the raw input would give another prediction
+        model.set_pipeline(None);
+
+        assert_ne!(
+            model.evaluate(&data).unwrap(),
+            loaded.evaluate(&data).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_custom_functions_and_bad_files_are_rejected() {
+        let custom = Model::new(
+            vec![Layer::from(
+                |x| x.clone(),
+                |x| x.map(|_| 1.0),
+                DMatrix::zeros(1, 1),
+                DMatrix::zeros(1, 1),
+            )],
+            mse,
+            mse_derivative,
+        );
+
+        assert!(matches!(custom.to_json(), Err(NeuraError::Format(_))));
+
+        // The registry functions given by themselves are not named either
+        let unnamed = Model::<f32>::new(
+            vec![Layer::from(
+                relu,
+                relu_derivative,
+                DMatrix::zeros(1, 1),
+                DMatrix::zeros(1, 1),
+            )],
+            mse,
+            mse_derivative,
+        );
+
+        assert!(matches!(unnamed.to_json(), Err(NeuraError::Format(_))));
+
+        let json = model().to_json().unwrap();
+
+        assert!(matches!(
+            Model::<f32>::from_json(&json.replace("\"mse\"", "\"unknown\"")),
+            Err(NeuraError::Format(_))
+        ));
+        assert!(matches!(
+            Model::<f32>::from_json(&json.replace("\"rows\":2", "\"rows\":3")),
+            Err(NeuraError::Format(_))
+        ));
+        assert!(matches!(
+            Model::<f32>::from_json("{}"),
+            Err(NeuraError::Serialization(_))
+        ));
+        assert!(matches!(
+            Model::<f32>::load("missing_model.json"),
+            Err(NeuraError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,31 @@
+use nalgebra::DMatrix;
+
+use super::float::Float;
+
+// Outputs of the layers for one inference call, kept out of the layers so that
+// a shared model can evaluate concurrently. Reusing a workspace reuses its
+// matrices when the shapes match.
+#[derive(Debug, Clone, Default)]
+pub struct Workspace<T: Float = f32> {
+    pub(crate) raw_outputs: Vec<DMatrix<T>>,
+    pub(crate) activated_outputs: Vec<DMatrix<T>>,
+}
+
+impl<T: Float> Workspace<T> {
+    pub fn new() -> Self {
+        Self {
+            raw_outputs: Vec::new(),
+            activated_outputs: Vec::new(),
+        }
+    }
+
+    pub(crate) fn resize(&mut self, layers: usize) {
+        self.raw_outputs.resize(layers, DMatrix::zeros(0, 0));
+        self.activated_outputs.resize(layers, DMatrix::zeros(0, 0));
+    }
+
+    // Activated output of every layer of the last evaluation
+    pub fn get_outputs_reference(&self) -> &[DMatrix<T>] {
+        &self.activated_outputs
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+};
+
+use flate2::read::GzDecoder;
+use nalgebra::DMatrix;
+
+use crate::core::float::Float;
+
+use super::{csv_loader::Normalization, dataset::InMemoryDataset};
+
+// Row-major n-dimensional array read from the binary formats
+#[derive(Debug, Clone, PartialEq)]
+pub struct Array {
+    pub shape: Vec<usize>,
+    pub data: Vec<f64>,
+}
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Zip(zip::result::ZipError),
+    Json {
+        line: usize,
+        error: serde_json::Error,
+    },
+    Format(String),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "{}", error),
+            ReadError::Zip(error) => write!(f, "{}", error),
+            ReadError::Json { line, error } => write!(f, "Line {}: {}", line, error),
+            ReadError::Format(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        ReadError::Io(error)
+    }
+}
+
+impl From<zip::result::ZipError> for ReadError {
+    fn from(error: zip::result::ZipError) -> Self {
+        ReadError::Zip(error)
+    }
+}
+
+impl Array {
+    pub fn new(shape: Vec<usize>, data: Vec<f64>) -> Result<Self, ReadError> {
+        if shape.iter().product::<usize>() != data.len() {
+            return Err(ReadError::Format(format!(
+                "The shape {:?} does not hold {} values",
+                shape,
+                data.len()
+            )));
+        }
+
+        Ok(Self { shape, data })
+    }
+
+    // Number of entries along the first dimension
+    pub fn len(&self) -> usize {
+        self.shape.first().copied().unwrap_or(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Entry along the first dimension, flattened
+    pub fn row(&self, index: usize) -> &[f64] {
+        let size = self.data.len() / self.len().max(1);
+
+        &self.data[index * size..(index + 1) * size]
+    }
+}
+
+// Opens the file, decompressing it when it starts with the gzip magic number
+pub fn open_maybe_gzipped(file_path: &str) -> Result<Box<dyn BufRead>, ReadError> {
+    let mut reader = BufReader::new(File::open(file_path)?);
+
+    if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        return Ok(Box::new(BufReader::new(GzDecoder::new(reader))));
+    }
+
+    Ok(Box::new(reader))
+}
+
+pub fn read_maybe_gzipped(file_path: &str) -> Result<Vec<u8>, ReadError> {
+    let mut bytes = Vec::new();
+
+    open_maybe_gzipped(file_path)?.read_to_end(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+// Pairs every input entry with its target. With classes the targets are class
+// indices that get one-hot encoded, otherwise each target entry is kept as is.
+pub fn to_dataset<T: Float>(
+    inputs: &Array,
+    targets: &Array,
+    normalization: Normalization,
+    classes: Option<usize>,
+) -> Result<InMemoryDataset<T>, ReadError> {
+    if inputs.len() != targets.len() {
+        return Err(ReadError::Format(format!(
+            "There are {} inputs but {} targets",
+            inputs.len(),
+            targets.len()
+        )));
+    }
+
+    let mut x = Vec::with_capacity(inputs.len());
+    let mut y = Vec::with_capacity(targets.len());
+
+    for index in 0..inputs.len() {
+        let input = inputs.row(index);
+
+        x.push(DMatrix::from_iterator(
+            input.len(),
+            1,
+            input
+                .iter()
+                .map(|&value| T::cast(normalization.apply(value))),
+        ));
+
+        let target = targets.row(index);
+
+        y.push(match classes {
+            Some(classes) => one_hot(target, classes, index)?,
+            None => {
+                DMatrix::from_iterator(target.len(), 1, target.iter().map(|&value| T::cast(value)))
+            }
+        });
+    }
+
+    Ok(InMemoryDataset::from_samples(
+        x.into_iter().zip(y).collect(),
+    ))
+}
+
+pub fn one_hot<T: Float>(
+    target: &[f64],
+    classes: usize,
+    index: usize,
+) -> Result<DMatrix<T>, ReadError> {
+    match target {
+        [class] if class.fract() == 0.0 && *class >= 0.0 && (*class as usize) < classes => {
+            let mut one_hot = DMatrix::zeros(classes, 1);
+
+            one_hot[*class as usize] = T::one();
+
+            Ok(one_hot)
+        }
+        _ => Err(ReadError::Format(format!(
+            "Target {} ({:?}) is not a class in 0..{}",
+            index, target, classes
+        ))),
+    }
+}
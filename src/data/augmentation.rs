@@ -0,0 +1,258 @@
+use nalgebra::DMatrix;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+
+use crate::{
+    core::float::Float,
+    error::{check_shape, NeuraError},
+};
+
+use super::dataset::Batch;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Augmentation {
+    // Up to this many pixels in each direction
+    Shift(f64),
+    // Up to this many degrees in each direction
+    Rotation(f64),
+    // Zoom factor drawn from [min, max]
+    Scaling(f64, f64),
+    // Mirrors the image with this probability
+    HorizontalFlip(f64),
+    // Random displacement field smoothed by a gaussian of std sigma and scaled by
+    // alpha, as in Simard et al.
+    ElasticDistortion { alpha: f64, sigma: f64 },
+    // Adds noise with this std to every pixel
+    GaussianNoise(f64),
+}
+
+impl Augmentation {
+    // Fails on the parameters the augmentation can't draw from
+    fn check(&self) -> Result<(), NeuraError> {
+        let non_negative = |value: f64| value >= 0.0 && value.is_finite();
+
+        let valid = match *self {
+            Augmentation::Shift(max_pixels) => non_negative(max_pixels),
+            Augmentation::Rotation(max_degrees) => non_negative(max_degrees),
+            Augmentation::Scaling(min, max) => min > 0.0 && min <= max && max.is_finite(),
+            Augmentation::HorizontalFlip(probability) => (0.0..=1.0).contains(&probability),
+            Augmentation::ElasticDistortion { alpha, sigma } => {
+                alpha.is_finite() && sigma > 0.0 && sigma.is_finite()
+            }
+            Augmentation::GaussianNoise(std) => non_negative(std),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(NeuraError::InvalidArgument(format!(
+                "Invalid augmentation {:?}",
+                self
+            )))
+        }
+    }
+}
+
+// Applies the augmentations in order to every input of the training batches.
+// The inputs are images of width x height pixels flattened row by row.
+pub struct Augmenter {
+    augmentations: Vec<Augmentation>,
+    height: usize,
+    rng: StdRng,
+    width: usize,
+}
+
+impl Augmenter {
+    // The images can't be empty, and the ranges of the augmentations must be
+    // finite and ordered
+    pub fn new(
+        width: usize,
+        height: usize,
+        augmentations: Vec<Augmentation>,
+        seed: u64,
+    ) -> Result<Self, NeuraError> {
+        if width == 0 || height == 0 {
+            return Err(NeuraError::InvalidArgument(format!(
+                "Can't augment images of {}x{} pixels",
+                width, height
+            )));
+        }
+
+        for augmentation in augmentations.iter() {
+            augmentation.check()?;
+        }
+
+        Ok(Self {
+            augmentations,
+            height,
+            rng: StdRng::seed_from_u64(seed),
+            width,
+        })
+    }
+
+    pub fn augment_batch<T: Float>(&mut self, batch: &mut Batch<T>) -> Result<(), NeuraError> {
+        for input in batch.inputs.iter_mut() {
+            *input = self.augment(input)?;
+        }
+
+        Ok(())
+    }
+
+    // The input must be a column of width x height pixels
+    pub fn augment<T: Float>(&mut self, input: &DMatrix<T>) -> Result<DMatrix<T>, NeuraError> {
+        check_shape("augmented input", (self.width * self.height, 1), input)?;
+
+        let mut image: Vec<f64> = input.iter().map(|value| value.as_f64()).collect();
+
+        for augmentation in self.augmentations.clone() {
+            image = match augmentation {
+                Augmentation::Shift(max_pixels) => {
+                    let dx = self.rng.gen_range(-max_pixels..=max_pixels);
+                    let dy = self.rng.gen_range(-max_pixels..=max_pixels);
+
+                    self.remap(&image, |x, y| (x - dx, y - dy))
+                }
+                Augmentation::Rotation(max_degrees) => {
+                    let angle = self.rng.gen_range(-max_degrees..=max_degrees).to_radians();
+
+                    let (sin, cos) = angle.sin_cos();
+
+                    self.remap_around_center(&image, |x, y| (cos * x + sin * y, cos * y - sin * x))
+                }
+                Augmentation::Scaling(min, max) => {
+                    let factor = self.rng.gen_range(min..=max);
+
+                    self.remap_around_center(&image, |x, y| (x / factor, y / factor))
+                }
+                Augmentation::HorizontalFlip(probability) => {
+                    if self.rng.gen_bool(probability) {
+                        let last = (self.width - 1) as f64;
+
+                        self.remap(&image, |x, y| (last - x, y))
+                    } else {
+                        image
+                    }
+                }
+                Augmentation::ElasticDistortion { alpha, sigma } => {
+                    let dx = self.displacement_field(alpha, sigma);
+                    let dy = self.displacement_field(alpha, sigma);
+
+                    let width = self.width;
+
+                    self.remap(&image, |x, y| {
+                        let i = y as usize * width + x as usize;
+
+                        (x + dx[i], y + dy[i])
+                    })
+                }
+                Augmentation::GaussianNoise(std) => {
+                    let normal = Normal::new(0.0, std)
+                        .map_err(|error| NeuraError::InvalidArgument(error.to_string()))?;
+
+                    image
+                        .iter()
+                        .map(|pixel| pixel + normal.sample(&mut self.rng))
+                        .collect()
+                }
+            };
+        }
+
+        Ok(DMatrix::from_iterator(
+            input.nrows(),
+            input.ncols(),
+            image.into_iter().map(T::cast),
+        ))
+    }
+
+    // Every output pixel takes the bilinear interpolation of the input at the
+    // source coordinates, pixels outside the image are 0
+    fn remap(&self, image: &[f64], source: impl Fn(f64, f64) -> (f64, f64)) -> Vec<f64> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x as f64, y as f64)))
+            .map(|(x, y)| {
+                let (source_x, source_y) = source(x, y);
+
+                self.interpolate(image, source_x, source_y)
+            })
+            .collect()
+    }
+
+    // Source coordinates computed relative to the image center
+    fn remap_around_center(
+        &self,
+        image: &[f64],
+        source: impl Fn(f64, f64) -> (f64, f64),
+    ) -> Vec<f64> {
+        let center_x = (self.width - 1) as f64 / 2.0;
+        let center_y = (self.height - 1) as f64 / 2.0;
+
+        self.remap(image, |x, y| {
+            let (source_x, source_y) = source(x - center_x, y - center_y);
+
+            (source_x + center_x, source_y + center_y)
+        })
+    }
+
+    fn interpolate(&self, image: &[f64], x: f64, y: f64) -> f64 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        let pixel = |x: f64, y: f64| {
+            if x < 0.0 || y < 0.0 || x >= self.width as f64 || y >= self.height as f64 {
+                0.0
+            } else {
+                image[y as usize * self.width + x as usize]
+            }
+        };
+
+        pixel(x0, y0) * (1.0 - tx) * (1.0 - ty)
+            + pixel(x0 + 1.0, y0) * tx * (1.0 - ty)
+            + pixel(x0, y0 + 1.0) * (1.0 - tx) * ty
+            + pixel(x0 + 1.0, y0 + 1.0) * tx * ty
+    }
+
+    fn displacement_field(&mut self, alpha: f64, sigma: f64) -> Vec<f64> {
+        let field: Vec<f64> = (0..self.width * self.height)
+            .map(|_| self.rng.gen_range(-1.0..=1.0))
+            .collect();
+
+        let radius = (3.0 * sigma).ceil() as i64;
+        let kernel: Vec<f64> = (-radius..=radius)
+            .map(|i| (-((i * i) as f64) / (2.0 * sigma * sigma)).exp())
+            .collect();
+        let kernel_sum: f64 = kernel.iter().sum();
+
+        // The gaussian is separable, so the rows and then the columns are blurred
+        let blur = |field: &[f64], step: usize, length: usize| -> Vec<f64> {
+            (0..field.len())
+                .map(|i| {
+                    let position = (i / step % length) as i64;
+
+                    kernel
+                        .iter()
+                        .enumerate()
+                        .map(|(k, weight)| {
+                            let neighbour = position + k as i64 - radius;
+
+                            if neighbour < 0 || neighbour >= length as i64 {
+                                0.0
+                            } else {
+                                let offset = (neighbour - position) * step as i64;
+
+                                weight * field[(i as i64 + offset) as usize]
+                            }
+                        })
+                        .sum::<f64>()
+                        / kernel_sum
+                })
+                .collect()
+        };
+
+        let field = blur(&field, 1, self.width);
+
+        blur(&field, self.width, self.height)
+            .into_iter()
+            .map(|displacement| displacement * alpha)
+            .collect()
+    }
+}
@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        data::{
+            augmentation::{Augmentation, Augmenter},
+            dataset::Batch,
+        },
+        error::NeuraError,
+    };
+
+    // 5x5 image with a single lit pixel
+    fn image(x: usize, y: usize) -> DMatrix<f64> {
+        let mut image = DMatrix::zeros(25, 1);
+
+        image[y * 5 + x] = 1.0;
+
+        image
+    }
+
+    #[test]
+    fn test_geometric_augmentations() {
+        let flip = Augmenter::new(5, 5, vec![Augmentation::HorizontalFlip(1.0)], 0)
+            .unwrap()
+            .augment(&image(1, 2))
+            .unwrap();
+
+        assert_eq!(image(3, 2), flip);
+
+        // No shift, no zoom
+        let identity = Augmenter::new(
+            5,
+            5,
+            vec![
+                Augmentation::Shift(0.0),
+                Augmentation::Rotation(0.0),
+                Augmentation::Scaling(1.0, 1.0),
+            ],
+            0,
+        )
+        .unwrap()
+        .augment(&image(1, 2))
+        .unwrap();
+
+        assert_eq!(image(1, 2), identity);
+
+        // Any angle keeps the center in place
+        let rotated = Augmenter::new(5, 5, vec![Augmentation::Rotation(90.0)], 0)
+            .unwrap()
+            .augment(&image(2, 2))
+            .unwrap();
+
+        assert!((rotated[12] - 1.0).abs() < 1e-12);
+
+        let zoomed = Augmenter::new(5, 5, vec![Augmentation::Scaling(2.0, 2.0)], 0)
+            .unwrap()
+            .augment(&image(2, 2))
+            .unwrap();
+
+        assert_eq!(1.0, zoomed[12]);
+        assert_eq!(0.5, zoomed[11]);
+    }
+
+    #[test]
+    fn test_augmentations_are_seeded() {
+        let augmentations = vec![
+            Augmentation::Shift(1.5),
+            Augmentation::Rotation(15.0),
+            Augmentation::ElasticDistortion {
+                alpha: 2.0,
+                sigma: 1.0,
+            },
+            Augmentation::GaussianNoise(0.1),
+        ];
+
+        let mut batch = Batch {
+            inputs: vec![image(2, 2), image(1, 3)],
+            targets: vec![DMatrix::zeros(1, 1); 2],
+        };
+        let mut same_seed_batch = batch.clone();
+        let original = batch.clone();
+
+        Augmenter::new(5, 5, augmentations.clone(), 7)
+            .unwrap()
+            .augment_batch(&mut batch)
+            .unwrap();
+        Augmenter::new(5, 5, augmentations.clone(), 7)
+            .unwrap()
+            .augment_batch(&mut same_seed_batch)
+            .unwrap();
+
+        assert_eq!(batch.inputs, same_seed_batch.inputs);
+        assert_ne!(original.inputs, batch.inputs);
+        assert_eq!(original.targets, batch.targets);
+
+        let other_seed = Augmenter::new(5, 5, augmentations, 8)
+            .unwrap()
+            .augment(&image(2, 2))
+            .unwrap();
+
+        assert_ne!(batch.inputs[0], other_seed);
+    }
+
+    #[test]
+    fn test_wrong_input_size() {
+        let result = Augmenter::new(5, 5, vec![], 0)
+            .unwrap()
+            .augment(&DMatrix::<f32>::zeros(4, 1));
+
+        assert_eq!(
+            "The augmented input should be 25x1 but is 4x1",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_invalid_parameters_are_errors() {
+        let invalid = |width: usize, height: usize, augmentation: Augmentation| {
+            matches!(
+                Augmenter::new(width, height, vec![augmentation], 0),
+                Err(NeuraError::InvalidArgument(_))
+            )
+        };
+
+        assert!(invalid(0, 5, Augmentation::Shift(1.0)));
+        assert!(invalid(5, 0, Augmentation::Shift(1.0)));
+        assert!(invalid(5, 5, Augmentation::Shift(-1.0)));
+        assert!(invalid(5, 5, Augmentation::Shift(f64::NAN)));
+        assert!(invalid(5, 5, Augmentation::Rotation(-10.0)));
+        assert!(invalid(5, 5, Augmentation::Rotation(f64::NAN)));
+        assert!(invalid(5, 5, Augmentation::Scaling(1.1, 0.9)));
+        assert!(invalid(5, 5, Augmentation::Scaling(0.0, 1.0)));
+        assert!(invalid(5, 5, Augmentation::HorizontalFlip(1.5)));
+        assert!(invalid(5, 5, Augmentation::HorizontalFlip(-0.1)));
+        assert!(invalid(
+            5,
+            5,
+            Augmentation::ElasticDistortion {
+                alpha: 2.0,
+                sigma: 0.0
+            }
+        ));
+        assert!(invalid(
+            5,
+            5,
+            Augmentation::ElasticDistortion {
+                alpha: f64::NAN,
+                sigma: 1.0
+            }
+        ));
+        assert!(invalid(5, 5, Augmentation::GaussianNoise(-0.1)));
+        assert!(invalid(5, 5, Augmentation::GaussianNoise(f64::NAN)));
+
+        assert!(!invalid(5, 5, Augmentation::Scaling(1.0, 1.0)));
+    }
+}
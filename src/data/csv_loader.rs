@@ -0,0 +1,280 @@
+use std::{error::Error, fmt, fs::File};
+
+use csv::{Reader, ReaderBuilder, StringRecord};
+use nalgebra::DMatrix;
+
+use crate::{core::float::Float, preprocessing::encoders::LabelEncoder};
+
+use super::dataset::InMemoryDataset;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Features {
+    // Every column except the label one
+    AllColumns,
+    Columns(Vec<usize>),
+    // A single column holding the features as a list, e.g. "[0, 255, 12]"
+    Array(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Normalization {
+    None,
+    // Divides every feature by the value, e.g. 255 for pixels
+    Divide(f64),
+    // Maps [min, max] to [0, 1]
+    Range(f64, f64),
+}
+
+impl Normalization {
+    pub fn apply(&self, value: f64) -> f64 {
+        match self {
+            Normalization::None => value,
+            Normalization::Divide(divisor) => value / divisor,
+            Normalization::Range(min, max) => (value - min) / (max - min),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvFormat {
+    pub label_column: usize,
+    pub features: Features,
+    pub has_headers: bool,
+    pub delimiter: u8,
+    pub normalization: Normalization,
+    // One-hot encodes the label as one of the classes, None keeps the label value
+    // as a 1x1 target for regressions
+    pub classes: Option<usize>,
+    // Maps the label names to the classes, None parses the labels as class indices
+    pub labels: Option<LabelEncoder>,
+}
+
+#[derive(Debug)]
+pub enum CsvError {
+    Csv(csv::Error),
+    MissingColumn {
+        line: u64,
+        column: usize,
+    },
+    InvalidNumber {
+        line: u64,
+        column: usize,
+        value: String,
+    },
+    InvalidClass {
+        line: u64,
+        value: String,
+        classes: usize,
+    },
+    FeaturesCountMismatch {
+        line: u64,
+        expected: usize,
+        found: usize,
+    },
+    Empty,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Csv(error) => write!(f, "{}", error),
+            CsvError::MissingColumn { line, column } => {
+                write!(f, "Line {}: missing column {}", line, column)
+            }
+            CsvError::InvalidNumber {
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "Line {}: column {} has the invalid number '{}'",
+                line, column, value
+            ),
+            CsvError::InvalidClass {
+                line,
+                value,
+                classes,
+            } => write!(
+                f,
+                "Line {}: '{}' is not a class in 0..{}",
+                line, value, classes
+            ),
+            CsvError::FeaturesCountMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {}: expected {} features but found {}",
+                line, expected, found
+            ),
+            CsvError::Empty => write!(f, "The file has no records"),
+        }
+    }
+}
+
+impl Error for CsvError {}
+
+impl From<csv::Error> for CsvError {
+    fn from(error: csv::Error) -> Self {
+        CsvError::Csv(error)
+    }
+}
+
+impl CsvFormat {
+    pub fn reader(&self, file_path: &str) -> Result<Reader<File>, CsvError> {
+        Ok(ReaderBuilder::new()
+            .has_headers(self.has_headers)
+            .delimiter(self.delimiter)
+            .from_path(file_path)?)
+    }
+
+    // The trimmed label of every record, e.g. to fit a LabelEncoder on
+    pub fn read_labels(&self, file_path: &str) -> Result<Vec<String>, CsvError> {
+        let mut reader = self.reader(file_path)?;
+        let mut record = StringRecord::new();
+        let mut labels = Vec::new();
+
+        while reader.read_record(&mut record)? {
+            let line = record.position().map_or(0, |position| position.line());
+
+            labels.push(field(&record, line, self.label_column)?.trim().to_string());
+        }
+
+        Ok(labels)
+    }
+
+    // (input, target) of a record
+    pub fn parse_record<T: Float>(
+        &self,
+        record: &StringRecord,
+    ) -> Result<(DMatrix<T>, DMatrix<T>), CsvError> {
+        let line = record.position().map_or(0, |position| position.line());
+
+        let features = match &self.features {
+            Features::AllColumns => (0..record.len())
+                .filter(|&column| column != self.label_column)
+                .map(|column| parse_number(line, column, &record[column]))
+                .collect::<Result<Vec<f64>, CsvError>>()?,
+            Features::Columns(columns) => columns
+                .iter()
+                .map(|&column| parse_number(line, column, field(record, line, column)?))
+                .collect::<Result<Vec<f64>, CsvError>>()?,
+            Features::Array(column) => field(record, line, *column)?
+                .trim()
+                .trim_matches(|p| p == '[' || p == ']')
+                .split(',')
+                .map(|value| parse_number(line, *column, value))
+                .collect::<Result<Vec<f64>, CsvError>>()?,
+        };
+
+        let input = DMatrix::from_iterator(
+            features.len(),
+            1,
+            features
+                .iter()
+                .map(|&value| T::cast(self.normalization.apply(value))),
+        );
+
+        let label = field(record, line, self.label_column)?.trim();
+
+        let target = match self.classes {
+            Some(classes) => {
+                let class = match &self.labels {
+                    Some(labels) => labels.transform(label),
+                    None => label.parse::<usize>().ok(),
+                };
+
+                let class = class.filter(|&class| class < classes).ok_or_else(|| {
+                    CsvError::InvalidClass {
+                        line,
+                        value: label.to_string(),
+                        classes,
+                    }
+                })?;
+
+                let mut target = DMatrix::zeros(classes, 1);
+
+                target[class] = T::one();
+
+                target
+            }
+            None => {
+                DMatrix::from_element(1, 1, T::cast(parse_number(line, self.label_column, label)?))
+            }
+        };
+
+        Ok((input, target))
+    }
+}
+
+fn field(record: &StringRecord, line: u64, column: usize) -> Result<&str, CsvError> {
+    record
+        .get(column)
+        .ok_or(CsvError::MissingColumn { line, column })
+}
+
+fn parse_number(line: u64, column: usize, value: &str) -> Result<f64, CsvError> {
+    value
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| CsvError::InvalidNumber {
+            line,
+            column,
+            value: value.trim().to_string(),
+        })
+}
+
+// Checks that every record has as many features as the first one
+pub fn check_features_count(
+    expected: &mut Option<usize>,
+    found: usize,
+    line: u64,
+) -> Result<(), CsvError> {
+    match expected {
+        Some(expected) if *expected != found => Err(CsvError::FeaturesCountMismatch {
+            line,
+            expected: *expected,
+            found,
+        }),
+        Some(_) => Ok(()),
+        None => {
+            *expected = Some(found);
+
+            Ok(())
+        }
+    }
+}
+
+pub fn load_csv<T: Float>(
+    file_path: &str,
+    format: &CsvFormat,
+) -> Result<InMemoryDataset<T>, CsvError> {
+    let mut reader = format.reader(file_path)?;
+    let mut record = StringRecord::new();
+
+    let mut x = Vec::new();
+    let mut y = Vec::new();
+    let mut features_count = None;
+
+    while reader.read_record(&mut record)? {
+        let (input, target) = format.parse_record::<T>(&record)?;
+
+        check_features_count(
+            &mut features_count,
+            input.len(),
+            record.position().map_or(0, |position| position.line()),
+        )?;
+
+        x.push(input);
+        y.push(target);
+    }
+
+    if x.is_empty() {
+        return Err(CsvError::Empty);
+    }
+
+    Ok(InMemoryDataset::from_samples(
+        x.into_iter().zip(y).collect(),
+    ))
+}
@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use nalgebra::DMatrix;
+
+    use crate::data::{
+        csv_loader::{load_csv, CsvError, CsvFormat, Features, Normalization},
+        dataset::Dataset,
+    };
+    use crate::preprocessing::encoders::LabelEncoder;
+
+    fn write_csv(name: &str, content: &str) -> String {
+        let path = env::temp_dir().join(format!("neura_rust_loader_{}.csv", name));
+
+        fs::write(&path, content).unwrap();
+
+        path.to_str().unwrap().to_string()
+    }
+
+    fn format(features: Features) -> CsvFormat {
+        CsvFormat {
+            label_column: 0,
+            features,
+            has_headers: true,
+            delimiter: b',',
+            normalization: Normalization::None,
+            classes: Some(3),
+            labels: None,
+        }
+    }
+
+    #[test]
+    fn test_load_feature_columns() {
+        let path = write_csv("columns", "label,a,b,c\n2,0,5,10\n0,10,0,5\n");
+
+        let mut format = format(Features::Columns(vec![1, 3]));
+        format.normalization = Normalization::Range(0.0, 10.0);
+
+        let dataset = load_csv::<f32>(&path, &format).unwrap();
+
+        assert_eq!(2, dataset.len());
+
+        let (input, target) = dataset.get(0).unwrap();
+
+        assert_eq!(DMatrix::from_vec(2, 1, vec![0.0, 1.0]), input);
+        assert_eq!(DMatrix::from_vec(3, 1, vec![0.0, 0.0, 1.0]), target);
+    }
+
+    #[test]
+    fn test_load_array_column_without_headers() {
+        let path = write_csv("array", "a;1;[0, 255, 51]\nb;0;[255,0,0]\n");
+
+        let format = CsvFormat {
+            label_column: 1,
+            features: Features::Array(2),
+            has_headers: false,
+            delimiter: b';',
+            normalization: Normalization::Divide(255.0),
+            classes: None,
+            labels: None,
+        };
+
+        let dataset = load_csv::<f64>(&path, &format).unwrap();
+
+        let (input, target) = dataset.get(0).unwrap();
+
+        assert_eq!(DMatrix::from_vec(3, 1, vec![0.0, 1.0, 0.2]), input);
+        assert_eq!(DMatrix::from_element(1, 1, 1.0), target);
+    }
+
+    #[test]
+    fn test_malformed_rows_are_reported() {
+        let load = |name: &str, content: &str, format: &CsvFormat| {
+            load_csv::<f32>(&write_csv(name, content), format)
+                .err()
+                .unwrap()
+        };
+
+        let columns = format(Features::AllColumns);
+
+        assert!(matches!(
+            load("number", "label,a,b\n0,1,2\n1,x,3\n", &columns),
+            CsvError::InvalidNumber { line: 3, column: 1, value } if value == "x"
+        ));
+        assert!(matches!(
+            load("class", "label,a,b\n0,1,2\n3,1,3\n", &columns),
+            CsvError::InvalidClass {
+                line: 3,
+                classes: 3,
+                ..
+            }
+        ));
+        assert!(matches!(
+            load("missing", "label,a,b\n0,1,2\n1,2\n", &columns),
+            CsvError::Csv(_)
+        ));
+        assert!(matches!(
+            load(
+                "count",
+                "label,a\n0,\"[1,2]\"\n1,[1]\n",
+                &format(Features::Array(1))
+            ),
+            CsvError::FeaturesCountMismatch {
+                line: 3,
+                expected: 2,
+                found: 1
+            }
+        ));
+        assert!(matches!(
+            load("empty", "label,a,b\n", &columns),
+            CsvError::Empty
+        ));
+    }
+
+    #[test]
+    fn test_load_named_labels() {
+        let path = write_csv(
+            "named",
+            "label,a
+cat,1
+dog,2
+cat,3
+fish,4
+",
+        );
+
+        let mut labels = LabelEncoder::new();
+        let mut format = format(Features::AllColumns);
+
+        labels.fit(
+            format
+                .read_labels(&path)
+                .unwrap()
+                .iter()
+                .map(String::as_str),
+        );
+
+        format.labels = Some(labels);
+
+        let dataset = load_csv::<f32>(&path, &format).unwrap();
+
+        assert_eq!(
+            DMatrix::from_vec(3, 1, vec![0.0, 1.0, 0.0]),
+            dataset.get(1).unwrap().1
+        );
+
+        format.classes = Some(2);
+
+        assert!(matches!(
+            load_csv::<f32>(&path, &format),
+            Err(CsvError::InvalidClass {
+                line: 5,
+                classes: 2,
+                ..
+            })
+        ));
+    }
+}
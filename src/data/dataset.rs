@@ -0,0 +1,125 @@
+use nalgebra::DMatrix;
+use rand::{seq::SliceRandom, thread_rng};
+
+use crate::{core::float::Float, error::NeuraError};
+
+#[derive(Clone)]
+pub struct Batch<T: Float = f32> {
+    pub inputs: Vec<DMatrix<T>>,
+    pub targets: Vec<DMatrix<T>>,
+}
+
+impl<T: Float> Batch<T> {
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+// Batches read lazily, stopping at the first sample that can't be read
+pub type Batches<'a, T> = Box<dyn Iterator<Item = Result<Batch<T>, NeuraError>> + 'a>;
+
+pub trait Dataset<T: Float = f32> {
+    fn len(&self) -> usize;
+
+    // (input, target) of the sample at index
+    fn get(&self, index: usize) -> Result<(DMatrix<T>, DMatrix<T>), NeuraError>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Lazily reads the samples of one batch at a time
+    fn batches(&self, batch_size: usize, shuffle: bool) -> Batches<'_, T> {
+        self.batches_in_order(sample_order(self.len(), shuffle), batch_size)
+    }
+
+    // Batches of the samples at the given indices, in that order
+    fn batches_in_order(&self, order: Vec<usize>, batch_size: usize) -> Batches<'_, T> {
+        let batch_size = batch_size.max(1);
+
+        Box::new((0..order.len()).step_by(batch_size).map(move |start| {
+            let end = (start + batch_size).min(order.len());
+
+            let samples = order[start..end]
+                .iter()
+                .map(|&index| self.get(index))
+                .collect::<Result<Vec<_>, NeuraError>>()?;
+
+            let (inputs, targets) = samples.into_iter().unzip();
+
+            Ok(Batch { inputs, targets })
+        }))
+    }
+
+    fn batches_count(&self, batch_size: usize) -> usize {
+        self.len().div_ceil(batch_size.max(1))
+    }
+}
+
+pub(crate) fn out_of_range(index: usize, len: usize) -> NeuraError {
+    NeuraError::InvalidArgument(format!(
+        "Index {} is out of a dataset of {} samples",
+        index, len
+    ))
+}
+
+pub fn sample_order(len: usize, shuffle: bool) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).collect();
+
+    if shuffle {
+        order.shuffle(&mut thread_rng());
+    }
+
+    order
+}
+
+pub struct InMemoryDataset<T: Float = f32> {
+    x: Vec<DMatrix<T>>,
+    y: Vec<DMatrix<T>>,
+}
+
+impl<T: Float> InMemoryDataset<T> {
+    pub fn new(x: Vec<DMatrix<T>>, y: Vec<DMatrix<T>>) -> Result<Self, NeuraError> {
+        if x.len() != y.len() {
+            return Err(NeuraError::InvalidArgument(format!(
+                "The dataset has {} inputs but {} targets",
+                x.len(),
+                y.len()
+            )));
+        }
+
+        Ok(Self { x, y })
+    }
+
+    // (input, target) pairs, which can't have a missing target
+    pub fn from_samples(samples: Vec<(DMatrix<T>, DMatrix<T>)>) -> Self {
+        let (x, y) = samples.into_iter().unzip();
+
+        Self { x, y }
+    }
+
+    pub fn get_inputs_reference(&self) -> &Vec<DMatrix<T>> {
+        &self.x
+    }
+
+    pub fn get_targets_reference(&self) -> &Vec<DMatrix<T>> {
+        &self.y
+    }
+}
+
+impl<T: Float> Dataset<T> for InMemoryDataset<T> {
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn get(&self, index: usize) -> Result<(DMatrix<T>, DMatrix<T>), NeuraError> {
+        match (self.x.get(index), self.y.get(index)) {
+            (Some(input), Some(target)) => Ok((input.clone(), target.clone())),
+            _ => Err(out_of_range(index, self.len())),
+        }
+    }
+}
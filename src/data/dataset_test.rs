@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        data::dataset::{Dataset, InMemoryDataset},
+        error::NeuraError,
+    };
+
+    fn dataset() -> InMemoryDataset {
+        InMemoryDataset::new(
+            (0..5)
+                .map(|i| DMatrix::from_element(2, 1, i as f32))
+                .collect(),
+            (0..5)
+                .map(|i| DMatrix::from_element(1, 1, i as f32 * 10.0))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_batches_in_order() {
+        let dataset = dataset();
+
+        let batches: Vec<_> = dataset.batches(2, false).collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(3, dataset.batches_count(2));
+        assert_eq!(
+            vec![2, 2, 1],
+            batches.iter().map(|batch| batch.len()).collect::<Vec<_>>()
+        );
+        assert_eq!(DMatrix::from_element(2, 1, 4.0), batches[2].inputs[0]);
+        assert_eq!(DMatrix::from_element(1, 1, 40.0), batches[2].targets[0]);
+    }
+
+    #[test]
+    fn test_shuffled_batches_keep_the_pairs() {
+        let dataset = dataset();
+
+        let mut seen: Vec<f32> = dataset
+            .batches(2, true)
+            .map(Result::unwrap)
+            .flat_map(|batch| {
+                batch
+                    .inputs
+                    .iter()
+                    .zip(batch.targets.iter())
+                    .map(|(input, target)| {
+                        assert_eq!(input[0] * 10.0, target[0]);
+
+                        input[0]
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        seen.sort_by(|a, b| a.total_cmp(b));
+
+        assert_eq!(vec![0.0, 1.0, 2.0, 3.0, 4.0], seen);
+    }
+
+    #[test]
+    fn test_mismatched_lengths() {
+        let result = InMemoryDataset::new(
+            vec![DMatrix::<f32>::zeros(1, 1), DMatrix::zeros(1, 1)],
+            vec![DMatrix::zeros(1, 1)],
+        );
+
+        assert!(matches!(
+            result,
+            Err(NeuraError::InvalidArgument(message))
+                if message == "The dataset has 2 inputs but 1 targets"
+        ));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::core::float::Float;
+
+use super::{
+    array::{read_maybe_gzipped, to_dataset, Array, ReadError},
+    csv_loader::Normalization,
+    dataset::InMemoryDataset,
+};
+
+// Reads an IDX file (the MNIST binary format), gzipped or not
+pub fn read_idx(file_path: &str) -> Result<Array, ReadError> {
+    parse_idx(&read_maybe_gzipped(file_path)?)
+        .map_err(|error| ReadError::Format(format!("{}: {}", file_path, error)))
+}
+
+// Big-endian header: two zero bytes, the element type, the number of dimensions
+// and then every dimension as an u32
+pub fn parse_idx(bytes: &[u8]) -> Result<Array, ReadError> {
+    if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 {
+        return Err(ReadError::Format("Missing IDX magic number".to_string()));
+    }
+
+    let element_size = match bytes[2] {
+        0x08 | 0x09 => 1,
+        0x0B => 2,
+        0x0C | 0x0D => 4,
+        0x0E => 8,
+        element_type => {
+            return Err(ReadError::Format(format!(
+                "Unknown IDX element type 0x{:02X}",
+                element_type
+            )))
+        }
+    };
+
+    let dimensions = bytes[3] as usize;
+    let data_start = 4 + dimensions * 4;
+
+    if bytes.len() < data_start {
+        return Err(ReadError::Format("Truncated IDX header".to_string()));
+    }
+
+    let shape: Vec<usize> = bytes[4..data_start]
+        .chunks_exact(4)
+        .map(|dimension| u32::from_be_bytes(dimension.try_into().unwrap()) as usize)
+        .collect();
+
+    let expected = shape.iter().product::<usize>() * element_size;
+
+    if bytes.len() - data_start != expected {
+        return Err(ReadError::Format(format!(
+            "Expected {} bytes of data for the shape {:?} but found {}",
+            expected,
+            shape,
+            bytes.len() - data_start
+        )));
+    }
+
+    let data = bytes[data_start..]
+        .chunks_exact(element_size)
+        .map(|element| match bytes[2] {
+            0x08 => element[0] as f64,
+            0x09 => element[0] as i8 as f64,
+            0x0B => i16::from_be_bytes(element.try_into().unwrap()) as f64,
+            0x0C => i32::from_be_bytes(element.try_into().unwrap()) as f64,
+            0x0D => f32::from_be_bytes(element.try_into().unwrap()) as f64,
+            _ => f64::from_be_bytes(element.try_into().unwrap()),
+        })
+        .collect();
+
+    Array::new(shape, data)
+}
+
+// MNIST-like pair of images and labels files, with the pixels scaled to [0, 1]
+pub fn load_mnist<T: Float>(
+    images_path: &str,
+    labels_path: &str,
+    classes: usize,
+) -> Result<InMemoryDataset<T>, ReadError> {
+    to_dataset(
+        &read_idx(images_path)?,
+        &read_idx(labels_path)?,
+        Normalization::Divide(255.0),
+        Some(classes),
+    )
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::Write};
+
+    use flate2::{write::GzEncoder, Compression};
+    use nalgebra::DMatrix;
+
+    use crate::data::{
+        dataset::Dataset,
+        idx::{load_mnist, parse_idx},
+    };
+
+    fn idx(element_type: u8, shape: &[u32], data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0, 0, element_type, shape.len() as u8];
+
+        shape
+            .iter()
+            .for_each(|dimension| bytes.extend(dimension.to_be_bytes()));
+        bytes.extend(data);
+
+        bytes
+    }
+
+    #[test]
+    fn test_parse_idx() {
+        let array = parse_idx(&idx(0x08, &[2, 2], &[0, 1, 2, 255])).unwrap();
+
+        assert_eq!(vec![2, 2], array.shape);
+        assert_eq!(vec![0.0, 1.0, 2.0, 255.0], array.data);
+
+        let data: Vec<u8> = [1.5_f32, -2.0]
+            .iter()
+            .flat_map(|x| x.to_be_bytes())
+            .collect();
+
+        assert_eq!(
+            vec![1.5, -2.0],
+            parse_idx(&idx(0x0D, &[2], &data)).unwrap().data
+        );
+
+        assert!(parse_idx(&idx(0x08, &[3], &[0, 1])).is_err());
+        assert!(parse_idx(&[8, 0, 0x08, 1]).is_err());
+    }
+
+    #[test]
+    fn test_load_gzipped_mnist() {
+        let images_path = env::temp_dir().join("neura_rust_images.idx.gz");
+        let labels_path = env::temp_dir().join("neura_rust_labels.idx");
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&idx(0x08, &[2, 1, 2], &[0, 255, 51, 0]))
+            .unwrap();
+
+        fs::write(&images_path, encoder.finish().unwrap()).unwrap();
+        fs::write(&labels_path, idx(0x08, &[2], &[1, 0])).unwrap();
+
+        let dataset = load_mnist::<f32>(
+            images_path.to_str().unwrap(),
+            labels_path.to_str().unwrap(),
+            3,
+        )
+        .unwrap();
+
+        assert_eq!(2, dataset.len());
+
+        let (input, target) = dataset.get(1).unwrap();
+
+        assert_eq!(DMatrix::from_vec(2, 1, vec![0.2, 0.0]), input);
+        assert_eq!(DMatrix::from_vec(3, 1, vec![1.0, 0.0, 0.0]), target);
+    }
+}
@@ -0,0 +1,19 @@
+pub mod array;
+pub mod augmentation;
+mod augmentation_test;
+pub mod csv_loader;
+mod csv_loader_test;
+pub mod dataset;
+mod dataset_test;
+pub mod idx;
+mod idx_test;
+pub mod npy;
+mod npy_test;
+pub mod quickdraw;
+mod quickdraw_test;
+pub mod sampling;
+mod sampling_test;
+pub mod split;
+mod split_test;
+pub mod streaming;
+mod streaming_test;
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, fs::File, io::Read};
+
+use zip::ZipArchive;
+
+use crate::core::float::Float;
+
+use super::{
+    array::{read_maybe_gzipped, to_dataset, Array, ReadError},
+    csv_loader::Normalization,
+    dataset::InMemoryDataset,
+};
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+pub fn read_npy(file_path: &str) -> Result<Array, ReadError> {
+    parse_npy(&read_maybe_gzipped(file_path)?)
+        .map_err(|error| ReadError::Format(format!("{}: {}", file_path, error)))
+}
+
+// Every array of the archive, keyed by its name without the .npy extension
+pub fn read_npz(file_path: &str) -> Result<HashMap<String, Array>, ReadError> {
+    let mut archive = ZipArchive::new(File::open(file_path)?)?;
+    let mut arrays = HashMap::with_capacity(archive.len());
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let mut bytes = Vec::with_capacity(file.size() as usize);
+
+        file.read_to_end(&mut bytes)?;
+
+        let name = file.name().trim_end_matches(".npy").to_string();
+
+        let array = parse_npy(&bytes)
+            .map_err(|error| ReadError::Format(format!("{}/{}: {}", file_path, name, error)))?;
+
+        arrays.insert(name, array);
+    }
+
+    Ok(arrays)
+}
+
+// Pairs the inputs and targets arrays of an archive, e.g. "x_train" and "y_train"
+pub fn load_npz<T: Float>(
+    file_path: &str,
+    inputs_name: &str,
+    targets_name: &str,
+    normalization: Normalization,
+    classes: Option<usize>,
+) -> Result<InMemoryDataset<T>, ReadError> {
+    let arrays = read_npz(file_path)?;
+
+    let array = |name: &str| {
+        arrays
+            .get(name)
+            .ok_or_else(|| ReadError::Format(format!("{} has no array {}", file_path, name)))
+    };
+
+    to_dataset(
+        array(inputs_name)?,
+        array(targets_name)?,
+        normalization,
+        classes,
+    )
+}
+
+// Format 1.0 has an u16 header length, 2.0 and 3.0 an u32 one. The header is a
+// python dict literal such as {'descr': '<f4', 'fortran_order': False, 'shape': (2, 3), }
+pub fn parse_npy(bytes: &[u8]) -> Result<Array, ReadError> {
+    if bytes.len() < 10 || !bytes.starts_with(MAGIC) {
+        return Err(ReadError::Format("Missing NPY magic string".to_string()));
+    }
+
+    let (header_start, header_len) = match bytes[6] {
+        1 => (10, u16::from_le_bytes([bytes[8], bytes[9]]) as usize),
+        2 | 3 if bytes.len() >= 12 => (
+            12,
+            u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize,
+        ),
+        version => {
+            return Err(ReadError::Format(format!(
+                "Unsupported NPY version {}",
+                version
+            )))
+        }
+    };
+
+    let data_start = header_start + header_len;
+
+    if bytes.len() < data_start {
+        return Err(ReadError::Format("Truncated NPY header".to_string()));
+    }
+
+    let header = String::from_utf8_lossy(&bytes[header_start..data_start]);
+
+    let descr = header_value(&header, "descr")?.trim_matches(|c| c == '\'' || c == '"');
+    let fortran_order = header_value(&header, "fortran_order")? == "True";
+
+    let shape = header_value(&header, "shape")?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(str::trim)
+        .filter(|dimension| !dimension.is_empty())
+        .map(|dimension| {
+            dimension
+                .parse::<usize>()
+                .map_err(|_| ReadError::Format(format!("Invalid dimension '{}'", dimension)))
+        })
+        .collect::<Result<Vec<usize>, ReadError>>()?;
+
+    let data = parse_data(descr, &bytes[data_start..], shape.iter().product())?;
+
+    let data = if fortran_order && shape.len() > 1 {
+        to_row_major(&shape, &data)
+    } else {
+        data
+    };
+
+    Array::new(shape, data)
+}
+
+// Raw text of a header key, up to the next top level comma
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, ReadError> {
+    let start = ["'", "\""]
+        .iter()
+        .find_map(|quote| header.find(&format!("{}{}{}", quote, key, quote)))
+        .ok_or_else(|| ReadError::Format(format!("The NPY header has no {}", key)))?;
+
+    let value = header[start + key.len() + 2..]
+        .trim_start()
+        .trim_start_matches(':')
+        .trim_start();
+
+    let end = if value.starts_with('(') {
+        value.find(')').map(|end| end + 1)
+    } else {
+        value.find([',', '}'])
+    }
+    .unwrap_or(value.len());
+
+    Ok(value[..end].trim())
+}
+
+fn parse_data(descr: &str, bytes: &[u8], count: usize) -> Result<Vec<f64>, ReadError> {
+    let unsupported = || ReadError::Format(format!("Unsupported dtype '{}'", descr));
+
+    // None for an empty descr or one starting with a multi-byte character
+    let (order, kind) = descr.get(..1).zip(descr.get(1..)).ok_or_else(unsupported)?;
+
+    let (kind, big_endian) = match order {
+        "<" | "|" | "=" => (kind, false),
+        ">" => (kind, true),
+        _ => (descr, false),
+    };
+
+    let size = kind
+        .get(1..)
+        .and_then(|size| size.parse::<usize>().ok())
+        .ok_or_else(unsupported)?;
+
+    if bytes.len() != count * size {
+        return Err(ReadError::Format(format!(
+            "Expected {} bytes of data but found {}",
+            count * size,
+            bytes.len()
+        )));
+    }
+
+    macro_rules! decode {
+        ($type:ty) => {
+            bytes
+                .chunks_exact(size)
+                .map(|element| {
+                    let element = element.try_into().unwrap();
+
+                    (if big_endian {
+                        <$type>::from_be_bytes(element)
+                    } else {
+                        <$type>::from_le_bytes(element)
+                    }) as f64
+                })
+                .collect()
+        };
+    }
+
+    Ok(match &kind[..1] {
+        "b" | "u" if size == 1 => bytes.iter().map(|&value| value as f64).collect(),
+        "i" if size == 1 => bytes.iter().map(|&value| value as i8 as f64).collect(),
+        "u" if size == 2 => decode!(u16),
+        "u" if size == 4 => decode!(u32),
+        "u" if size == 8 => decode!(u64),
+        "i" if size == 2 => decode!(i16),
+        "i" if size == 4 => decode!(i32),
+        "i" if size == 8 => decode!(i64),
+        "f" if size == 2 => bytes
+            .chunks_exact(2)
+            .map(|element| {
+                let element = element.try_into().unwrap();
+
+                (if big_endian {
+                    half::f16::from_be_bytes(element)
+                } else {
+                    half::f16::from_le_bytes(element)
+                })
+                .to_f64()
+            })
+            .collect(),
+        "f" if size == 4 => decode!(f32),
+        "f" if size == 8 => decode!(f64),
+        _ => return Err(unsupported()),
+    })
+}
+
+// Fortran arrays have the first index varying the fastest
+fn to_row_major(shape: &[usize], data: &[f64]) -> Vec<f64> {
+    let mut strides = vec![1; shape.len()];
+
+    for dimension in 1..shape.len() {
+        strides[dimension] = strides[dimension - 1] * shape[dimension - 1];
+    }
+
+    (0..data.len())
+        .map(|mut index| {
+            let mut fortran_index = 0;
+
+            for dimension in (0..shape.len()).rev() {
+                fortran_index += (index % shape[dimension]) * strides[dimension];
+                index /= shape[dimension];
+            }
+
+            data[fortran_index]
+        })
+        .collect()
+}
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs::File, io::Write};
+
+    use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+    use crate::data::{
+        array::ReadError,
+        csv_loader::Normalization,
+        dataset::Dataset,
+        npy::{load_npz, parse_npy},
+    };
+
+    fn npy(header: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+
+        bytes.extend((header.len() as u16).to_le_bytes());
+        bytes.extend(header.as_bytes());
+        bytes.extend(data);
+
+        bytes
+    }
+
+    #[test]
+    fn test_parse_npy() {
+        let data: Vec<u8> = [1.0_f32, 2.0, 3.0, 4.0, 5.0, 6.0]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+
+        let array = parse_npy(&npy(
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3), }",
+            &data,
+        ))
+        .unwrap();
+
+        assert_eq!(vec![2, 3], array.shape);
+        assert_eq!(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], array.data);
+        assert_eq!(&[4.0, 5.0, 6.0], array.row(1));
+
+        // Columns are stored one after the other
+        let array = parse_npy(&npy(
+            "{'descr': '<f4', 'fortran_order': True, 'shape': (2, 3), }",
+            &data,
+        ))
+        .unwrap();
+
+        assert_eq!(vec![1.0, 3.0, 5.0, 2.0, 4.0, 6.0], array.data);
+
+        let data: Vec<u8> = [-1_i16, 300].iter().flat_map(|x| x.to_be_bytes()).collect();
+
+        let array = parse_npy(&npy(
+            "{'descr': '>i2', 'fortran_order': False, 'shape': (2,), }",
+            &data,
+        ))
+        .unwrap();
+
+        assert_eq!(vec![-1.0, 300.0], array.data);
+
+        assert!(parse_npy(&npy(
+            "{'descr': '<c8', 'fortran_order': False, 'shape': (1,), }",
+            &[0; 8]
+        ))
+        .is_err());
+
+        for descr in ["", "é4"] {
+            let header = format!(
+                "{{'descr': '{}', 'fortran_order': False, 'shape': (1,), }}",
+                descr
+            );
+
+            assert!(matches!(
+                parse_npy(&npy(&header, &[0; 4])),
+                Err(ReadError::Format(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_load_npz() {
+        let path = env::temp_dir().join("neura_rust_arrays.npz");
+
+        let mut writer = ZipWriter::new(File::create(&path).unwrap());
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        writer.start_file("x_train.npy", options).unwrap();
+        writer
+            .write_all(&npy(
+                "{'descr': '|u1', 'fortran_order': False, 'shape': (2, 2), }",
+                &[0, 255, 255, 0],
+            ))
+            .unwrap();
+
+        writer.start_file("y_train.npy", options).unwrap();
+        writer
+            .write_all(&npy(
+                "{'descr': '<i8', 'fortran_order': False, 'shape': (2,), }",
+                &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            ))
+            .unwrap();
+
+        writer.finish().unwrap();
+
+        let path = path.to_str().unwrap();
+
+        let dataset = load_npz::<f32>(
+            path,
+            "x_train",
+            "y_train",
+            Normalization::Divide(255.0),
+            Some(2),
+        )
+        .unwrap();
+
+        let (input, target) = dataset.get(0).unwrap();
+
+        assert_eq!(vec![0.0, 1.0], input.as_slice());
+        assert_eq!(vec![0.0, 1.0], target.as_slice());
+
+        assert!(load_npz::<f32>(path, "x_test", "y_train", Normalization::None, None).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use std::io::BufRead;
+
+use nalgebra::DMatrix;
+use serde::Deserialize;
+
+use crate::core::float::Float;
+
+use super::{
+    array::{open_maybe_gzipped, ReadError},
+    dataset::InMemoryDataset,
+};
+
+// One line of the Quick, Draw! ndjson files. Every stroke is a list of the x
+// coordinates, the y coordinates and, in the raw files, the timestamps.
+#[derive(Debug, Deserialize)]
+pub struct Drawing {
+    pub word: String,
+    #[serde(default = "recognized_by_default")]
+    pub recognized: bool,
+    pub drawing: Vec<Vec<Vec<f64>>>,
+}
+
+fn recognized_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickDrawOptions {
+    // The label of a drawing is the position of its word in this list
+    pub classes: Vec<String>,
+    // Side of the rasterized bitmaps
+    pub size: usize,
+    pub recognized_only: bool,
+    // Maximum number of drawings read from each file
+    pub limit: Option<usize>,
+}
+
+// Reads ndjson files, gzipped or not, into size x size bitmaps flattened row by row
+pub fn load_quickdraw<T: Float>(
+    file_paths: &[&str],
+    options: &QuickDrawOptions,
+) -> Result<InMemoryDataset<T>, ReadError> {
+    let mut x = Vec::new();
+    let mut y = Vec::new();
+
+    for file_path in file_paths {
+        let mut count = 0;
+
+        for (line, text) in open_maybe_gzipped(file_path)?.lines().enumerate() {
+            if options.limit.is_some_and(|limit| count >= limit) {
+                break;
+            }
+
+            let text = text?;
+
+            if text.trim().is_empty() {
+                continue;
+            }
+
+            let drawing: Drawing =
+                serde_json::from_str(&text).map_err(|error| ReadError::Json {
+                    line: line + 1,
+                    error,
+                })?;
+
+            if options.recognized_only && !drawing.recognized {
+                continue;
+            }
+
+            let class = options
+                .classes
+                .iter()
+                .position(|class| *class == drawing.word)
+                .ok_or_else(|| {
+                    ReadError::Format(format!(
+                        "Line {} of {}: '{}' is not one of the classes",
+                        line + 1,
+                        file_path,
+                        drawing.word
+                    ))
+                })?;
+
+            let mut target = DMatrix::zeros(options.classes.len(), 1);
+
+            target[class] = T::one();
+
+            x.push(rasterize(&drawing.drawing, options.size));
+            y.push(target);
+
+            count += 1;
+        }
+    }
+
+    if x.is_empty() {
+        return Err(ReadError::Format("No drawings were read".to_string()));
+    }
+
+    Ok(InMemoryDataset::from_samples(
+        x.into_iter().zip(y).collect(),
+    ))
+}
+
+// Scales the drawing to fit the bitmap keeping its aspect ratio, centers it and
+// draws every stroke segment with ones over a zero background
+pub fn rasterize<T: Float>(strokes: &[Vec<Vec<f64>>], size: usize) -> DMatrix<T> {
+    let mut bitmap = DMatrix::zeros(size * size, 1);
+
+    let points = strokes
+        .iter()
+        .filter(|stroke| stroke.len() >= 2)
+        .flat_map(|stroke| stroke[0].iter().zip(stroke[1].iter()));
+
+    let (min_x, min_y, max_x, max_y) = points.fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(min_x, min_y, max_x, max_y), (&x, &y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    );
+
+    if size == 0 || min_x > max_x {
+        return bitmap;
+    }
+
+    let last = (size - 1) as f64;
+    let extent = (max_x - min_x).max(max_y - min_y);
+    let scale = if extent > 0.0 { last / extent } else { 0.0 };
+
+    let offset_x = (last - (max_x - min_x) * scale) / 2.0;
+    let offset_y = (last - (max_y - min_y) * scale) / 2.0;
+
+    let pixel = |x: f64, y: f64| {
+        (
+            ((x - min_x) * scale + offset_x).round() as i64,
+            ((y - min_y) * scale + offset_y).round() as i64,
+        )
+    };
+
+    for stroke in strokes.iter().filter(|stroke| stroke.len() >= 2) {
+        let pixels: Vec<(i64, i64)> = stroke[0]
+            .iter()
+            .zip(stroke[1].iter())
+            .map(|(&x, &y)| pixel(x, y))
+            .collect();
+
+        if let [only] = pixels.as_slice() {
+            draw_line(&mut bitmap, size, *only, *only);
+        }
+
+        for segment in pixels.windows(2) {
+            draw_line(&mut bitmap, size, segment[0], segment[1]);
+        }
+    }
+
+    bitmap
+}
+
+// Bresenham's line algorithm
+fn draw_line<T: Float>(bitmap: &mut DMatrix<T>, size: usize, from: (i64, i64), to: (i64, i64)) {
+    let (mut x, mut y) = from;
+
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let step_x = if x < to.0 { 1 } else { -1 };
+    let step_y = if y < to.1 { 1 } else { -1 };
+
+    let mut error = dx + dy;
+
+    loop {
+        bitmap[y as usize * size + x as usize] = T::one();
+
+        if (x, y) == to {
+            break;
+        }
+
+        let doubled_error = 2 * error;
+
+        if doubled_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+
+        if doubled_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use nalgebra::DMatrix;
+
+    use crate::data::{
+        array::ReadError,
+        dataset::Dataset,
+        quickdraw::{load_quickdraw, rasterize, QuickDrawOptions},
+    };
+
+    #[test]
+    fn test_rasterize() {
+        // A diagonal and a single point, on a 200 x 100 canvas
+        let strokes = vec![
+            vec![vec![0.0, 100.0], vec![0.0, 100.0]],
+            vec![vec![200.0], vec![0.0]],
+        ];
+
+        let bitmap: DMatrix<f32> = rasterize(&strokes, 5);
+
+        #[rustfmt::skip]
+        let expected = vec![
+            0.0, 0.0, 0.0, 0.0, 0.0,
+            1.0, 0.0, 0.0, 0.0, 1.0,
+            0.0, 1.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0, 0.0,
+        ];
+
+        assert_eq!(expected, bitmap.as_slice());
+    }
+
+    #[test]
+    fn test_load_quickdraw() {
+        let path = env::temp_dir().join("neura_rust_quickdraw.ndjson");
+
+        fs::write(
+            &path,
+            concat!(
+                r#"{"word":"cat","recognized":true,"drawing":[[[0,10],[0,10]]]}"#,
+                "\n",
+                r#"{"word":"dog","recognized":false,"drawing":[[[0,10],[10,0]]]}"#,
+                "\n",
+                r#"{"word":"dog","countrycode":"BR","drawing":[[[0,10,5],[10,0,5],[0,1,2]]]}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let path = path.to_str().unwrap();
+
+        let mut options = QuickDrawOptions {
+            classes: vec!["dog".to_string(), "cat".to_string()],
+            size: 4,
+            recognized_only: true,
+            limit: None,
+        };
+
+        let dataset = load_quickdraw::<f32>(&[path], &options).unwrap();
+
+        assert_eq!(2, dataset.len());
+        assert_eq!(16, dataset.get(0).unwrap().0.len());
+        assert_eq!(vec![0.0, 1.0], dataset.get(0).unwrap().1.as_slice());
+        assert_eq!(vec![1.0, 0.0], dataset.get(1).unwrap().1.as_slice());
+
+        options.limit = Some(1);
+
+        assert_eq!(1, load_quickdraw::<f32>(&[path], &options).unwrap().len());
+
+        options.classes.pop();
+        options.limit = None;
+
+        assert!(matches!(
+            load_quickdraw::<f32>(&[path], &options),
+            Err(ReadError::Format(_))
+        ));
+    }
+}
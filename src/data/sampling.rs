@@ -0,0 +1,106 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{core::float::Float, error::NeuraError};
+
+use super::{dataset::Dataset, split::target_class};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sampling {
+    // Draws the samples of the smaller classes again until every class has as
+    // many samples as the largest one
+    Oversample,
+    // Keeps a random part of the larger classes, as many samples as the smallest one has
+    Undersample,
+}
+
+// Picks the samples of every epoch of fit so the classes are balanced
+pub struct BatchSampler {
+    rng: StdRng,
+    sampling: Sampling,
+}
+
+impl BatchSampler {
+    pub fn new(sampling: Sampling, seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            sampling,
+        }
+    }
+
+    // Shuffled sample indices of one epoch, given the samples of each class
+    pub fn epoch_order(&mut self, samples_by_class: &[Vec<usize>]) -> Vec<usize> {
+        let classes = samples_by_class
+            .iter()
+            .filter(|samples| !samples.is_empty());
+
+        let class_len = match self.sampling {
+            Sampling::Oversample => classes.map(Vec::len).max(),
+            Sampling::Undersample => classes.map(Vec::len).min(),
+        }
+        .unwrap_or(0);
+
+        let mut order = Vec::with_capacity(class_len * samples_by_class.len());
+
+        for samples in samples_by_class
+            .iter()
+            .filter(|samples| !samples.is_empty())
+        {
+            let mut samples = samples.clone();
+
+            samples.shuffle(&mut self.rng);
+
+            order.extend(samples.iter().take(class_len));
+
+            for _ in samples.len()..class_len {
+                order.push(samples[self.rng.gen_range(0..samples.len())]);
+            }
+        }
+
+        order.shuffle(&mut self.rng);
+
+        order
+    }
+}
+
+// Indices of the samples of each class, indexed by class
+pub fn samples_by_class<T: Float>(dataset: &dyn Dataset<T>) -> Result<Vec<Vec<usize>>, NeuraError> {
+    let mut classes: Vec<Vec<usize>> = Vec::new();
+    let mut index = 0;
+
+    for batch in dataset.batches(256, false) {
+        let batch = batch?;
+
+        for target in batch.targets.iter() {
+            let class = target_class(target);
+
+            if class >= classes.len() {
+                classes.resize(class + 1, Vec::new());
+            }
+
+            classes[class].push(index);
+
+            index += 1;
+        }
+    }
+
+    Ok(classes)
+}
+
+// Weights inversely proportional to the class frequencies, samples / (classes * class samples),
+// to be set with Model::set_class_weights. Absent classes get a weight of 0.
+pub fn balanced_class_weights<T: Float>(dataset: &dyn Dataset<T>) -> Result<Vec<T>, NeuraError> {
+    let classes = samples_by_class(dataset)?;
+
+    let present_classes = classes.iter().filter(|samples| !samples.is_empty()).count();
+
+    Ok(classes
+        .iter()
+        .map(|samples| {
+            if samples.is_empty() {
+                T::zero()
+            } else {
+                T::cast(dataset.len() as f64 / (present_classes * samples.len()) as f64)
+            }
+        })
+        .collect())
+}
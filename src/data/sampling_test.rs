@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        core::{
+            layer::Layer,
+            model::{FitOptions, Model},
+        },
+        data::{
+            dataset::InMemoryDataset,
+            sampling::{balanced_class_weights, samples_by_class, BatchSampler, Sampling},
+        },
+        functions::{
+            activations::{sigmoid, sigmoid_derivative},
+            losses::{mse, mse_derivative},
+        },
+        optimizers::rmsprop::RMSProp,
+    };
+
+    // 6 samples of class 0 and 2 of class 1
+    fn dataset() -> InMemoryDataset {
+        let x = (0..8)
+            .map(|i| DMatrix::from_element(1, 1, i as f32))
+            .collect();
+        let y = (0..8)
+            .map(|i| {
+                DMatrix::from_vec(
+                    2,
+                    1,
+                    if i < 6 {
+                        vec![1.0, 0.0]
+                    } else {
+                        vec![0.0, 1.0]
+                    },
+                )
+            })
+            .collect();
+
+        InMemoryDataset::new(x, y).unwrap()
+    }
+
+    #[test]
+    fn test_samplers_balance_the_classes() {
+        let classes = samples_by_class(&dataset()).unwrap();
+
+        assert_eq!(vec![vec![0, 1, 2, 3, 4, 5], vec![6, 7]], classes);
+
+        let oversampled = BatchSampler::new(Sampling::Oversample, 1).epoch_order(&classes);
+
+        assert_eq!(12, oversampled.len());
+        assert_eq!(6, oversampled.iter().filter(|&&index| index >= 6).count());
+        (0..8).for_each(|index| assert!(oversampled.contains(&index)));
+
+        let undersampled = BatchSampler::new(Sampling::Undersample, 1).epoch_order(&classes);
+
+        assert_eq!(4, undersampled.len());
+        assert_eq!(2, undersampled.iter().filter(|&&index| index >= 6).count());
+    }
+
+    #[test]
+    fn test_balanced_class_weights() {
+        let weights: Vec<f32> = balanced_class_weights(&dataset()).unwrap();
+
+        assert_eq!(vec![8.0 / 12.0, 2.0], weights);
+    }
+
+    #[test]
+    fn test_zero_weights_leave_the_model_unchanged() {
+        let mut model = Model::new(
+            vec![Layer::new(sigmoid, sigmoid_derivative, 1, 2)],
+            mse,
+            mse_derivative,
+        );
+        let weights = model.get_layers_reference()[0]
+            .get_weights_reference()
+            .clone();
+
+        model.set_class_weights(Some(vec![0.0, 1.0]));
+
+        let mut sampler = BatchSampler::new(Sampling::Oversample, 3);
+
+        model
+            .fit(
+                FitOptions {
+                    batch_size: 2,
+                    epochs: 2,
+                    learning_rate: 0.1,
+                    sample_weights: Some(&[1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0]),
+                    sampler: Some(&mut sampler),
+                    ..FitOptions::default()
+                },
+                &mut RMSProp::new(0.9),
+                &dataset(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &weights,
+            model.get_layers_reference()[0].get_weights_reference()
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use nalgebra::DMatrix;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{core::float::Float, error::NeuraError};
+
+use super::{
+    dataset::{out_of_range, Batches, Dataset},
+    sampling::samples_by_class,
+};
+
+pub type TrainValidation<'a, T> = (Subset<'a, T>, Subset<'a, T>);
+
+pub type TrainValidationTest<'a, T> = (Subset<'a, T>, Subset<'a, T>, Subset<'a, T>);
+
+// View of some samples of a dataset, read from it when requested
+pub struct Subset<'a, T: Float = f32> {
+    dataset: &'a dyn Dataset<T>,
+    indices: Vec<usize>,
+}
+
+impl<'a, T: Float> Subset<'a, T> {
+    pub fn new(dataset: &'a dyn Dataset<T>, indices: Vec<usize>) -> Result<Self, NeuraError> {
+        if let Some(&index) = indices.iter().find(|&&index| index >= dataset.len()) {
+            return Err(out_of_range(index, dataset.len()));
+        }
+
+        Ok(Self { dataset, indices })
+    }
+
+    pub fn get_indices_reference(&self) -> &Vec<usize> {
+        &self.indices
+    }
+}
+
+impl<T: Float> Dataset<T> for Subset<'_, T> {
+    fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    fn get(&self, index: usize) -> Result<(DMatrix<T>, DMatrix<T>), NeuraError> {
+        match self.indices.get(index) {
+            Some(&index) => self.dataset.get(index),
+            None => Err(out_of_range(index, self.len())),
+        }
+    }
+
+    fn batches_in_order(&self, order: Vec<usize>, batch_size: usize) -> Batches<'_, T> {
+        let order = order
+            .into_iter()
+            .map(|index| {
+                self.indices
+                    .get(index)
+                    .copied()
+                    .ok_or_else(|| out_of_range(index, self.len()))
+            })
+            .collect::<Result<Vec<usize>, NeuraError>>();
+
+        match order {
+            Ok(order) => self.dataset.batches_in_order(order, batch_size),
+            Err(error) => Box::new(std::iter::once(Err(error))),
+        }
+    }
+}
+
+// Class of a one-hot target, or of a single 0/1 target for binary problems
+pub fn target_class<T: Float>(target: &DMatrix<T>) -> usize {
+    if target.len() == 1 {
+        return target[0].as_f64().round().max(0.0) as usize;
+    }
+
+    target
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.as_f64().total_cmp(&b.1.as_f64()))
+        .map_or(0, |(class, _)| class)
+}
+
+// Shuffled indices of the samples of each class, reading the targets in order
+fn indices_by_class<T: Float>(
+    dataset: &dyn Dataset<T>,
+    rng: &mut StdRng,
+) -> Result<Vec<Vec<usize>>, NeuraError> {
+    Ok(samples_by_class(dataset)?
+        .into_iter()
+        .filter(|indices| !indices.is_empty())
+        .map(|mut indices| {
+            indices.shuffle(rng);
+
+            indices
+        })
+        .collect())
+}
+
+// Splits the samples of every class by the validation and test fractions, so the
+// three subsets keep the class proportions of the dataset
+pub fn train_validation_test_split<'a, T: Float>(
+    dataset: &'a dyn Dataset<T>,
+    validation_fraction: f64,
+    test_fraction: f64,
+    seed: u64,
+) -> Result<TrainValidationTest<'a, T>, NeuraError> {
+    // Written so that NaN fractions fail as well
+    if !(validation_fraction >= 0.0
+        && test_fraction >= 0.0
+        && validation_fraction + test_fraction <= 1.0)
+    {
+        return Err(NeuraError::InvalidArgument(format!(
+            "Can't split {} for validation and {} for test",
+            validation_fraction, test_fraction
+        )));
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut train = Vec::new();
+    let mut validation = Vec::new();
+    let mut test = Vec::new();
+
+    for indices in indices_by_class(dataset, &mut rng)? {
+        let test_len = (indices.len() as f64 * test_fraction).round() as usize;
+        let validation_len = ((indices.len() as f64 * validation_fraction).round() as usize)
+            .min(indices.len() - test_len);
+
+        test.extend_from_slice(&indices[..test_len]);
+        validation.extend_from_slice(&indices[test_len..test_len + validation_len]);
+        train.extend_from_slice(&indices[test_len + validation_len..]);
+    }
+
+    // Sorted indices keep reading streamed datasets sequentially
+    [&mut train, &mut validation, &mut test]
+        .iter_mut()
+        .for_each(|indices| indices.sort_unstable());
+
+    Ok((
+        Subset::new(dataset, train)?,
+        Subset::new(dataset, validation)?,
+        Subset::new(dataset, test)?,
+    ))
+}
+
+// (train, validation) subsets of each fold. The samples of every class are dealt
+// to the folds in turn, so each fold keeps the class proportions.
+pub fn stratified_k_fold<'a, T: Float>(
+    dataset: &'a dyn Dataset<T>,
+    folds: usize,
+    seed: u64,
+) -> Result<Vec<TrainValidation<'a, T>>, NeuraError> {
+    if folds < 2 {
+        return Err(NeuraError::InvalidArgument(format!(
+            "Cross-validation needs at least 2 folds, got {}",
+            folds
+        )));
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut fold_of_sample = vec![0; dataset.len()];
+    let mut next_fold = 0;
+
+    for indices in indices_by_class(dataset, &mut rng)? {
+        for index in indices {
+            fold_of_sample[index] = next_fold;
+
+            next_fold = (next_fold + 1) % folds;
+        }
+    }
+
+    (0..folds)
+        .map(|fold| {
+            let (validation, train): (Vec<usize>, Vec<usize>) =
+                (0..dataset.len()).partition(|&index| fold_of_sample[index] == fold);
+
+            Ok((
+                Subset::new(dataset, train)?,
+                Subset::new(dataset, validation)?,
+            ))
+        })
+        .collect()
+}
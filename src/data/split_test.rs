@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        data::{
+            dataset::{Dataset, InMemoryDataset},
+            split::{stratified_k_fold, target_class, train_validation_test_split, Subset},
+        },
+        error::NeuraError,
+    };
+
+    // 30 samples of class 0 and 10 of class 1, the input holds the sample index
+    fn dataset() -> InMemoryDataset {
+        let x = (0..40)
+            .map(|i| DMatrix::from_element(1, 1, i as f32))
+            .collect();
+        let y = (0..40)
+            .map(|i| {
+                DMatrix::from_vec(
+                    2,
+                    1,
+                    if i % 4 == 3 {
+                        vec![0.0, 1.0]
+                    } else {
+                        vec![1.0, 0.0]
+                    },
+                )
+            })
+            .collect();
+
+        InMemoryDataset::new(x, y).unwrap()
+    }
+
+    fn class_count(subset: &Subset, class: usize) -> usize {
+        subset
+            .batches(7, true)
+            .flat_map(|batch| batch.unwrap().targets)
+            .filter(|target| target_class(target) == class)
+            .count()
+    }
+
+    #[test]
+    fn test_subset_reads_the_parent_samples() {
+        let dataset = dataset();
+        let subset = Subset::new(&dataset, vec![5, 2, 9]).unwrap();
+
+        assert_eq!(3, subset.len());
+        assert_eq!(DMatrix::from_element(1, 1, 2.0), subset.get(1).unwrap().0);
+
+        let inputs: Vec<f32> = subset
+            .batches(2, false)
+            .flat_map(|batch| batch.unwrap().inputs)
+            .map(|input| input[0])
+            .collect();
+
+        assert_eq!(vec![5.0, 2.0, 9.0], inputs);
+    }
+
+    #[test]
+    fn test_stratified_split() {
+        let dataset = dataset();
+
+        let (train, validation, test) = train_validation_test_split(&dataset, 0.2, 0.1, 3).unwrap();
+
+        assert_eq!((28, 8, 4), (train.len(), validation.len(), test.len()));
+        assert_eq!(
+            (6, 2),
+            (class_count(&validation, 0), class_count(&validation, 1))
+        );
+        assert_eq!((3, 1), (class_count(&test, 0), class_count(&test, 1)));
+
+        let mut all: Vec<usize> = [&train, &validation, &test]
+            .iter()
+            .flat_map(|subset| subset.get_indices_reference().clone())
+            .collect();
+        all.sort();
+
+        assert_eq!((0..40).collect::<Vec<usize>>(), all);
+
+        let (same_train, _, _) = train_validation_test_split(&dataset, 0.2, 0.1, 3).unwrap();
+        let (other_train, _, _) = train_validation_test_split(&dataset, 0.2, 0.1, 4).unwrap();
+
+        assert_eq!(
+            train.get_indices_reference(),
+            same_train.get_indices_reference()
+        );
+        assert_ne!(
+            train.get_indices_reference(),
+            other_train.get_indices_reference()
+        );
+    }
+
+    #[test]
+    fn test_stratified_k_fold() {
+        let dataset = dataset();
+        let folds = stratified_k_fold(&dataset, 5, 0).unwrap();
+
+        let mut validated = Vec::new();
+
+        for (train, validation) in folds.iter() {
+            assert_eq!((32, 8), (train.len(), validation.len()));
+            assert_eq!(2, class_count(validation, 1));
+
+            validated.extend(validation.get_indices_reference().clone());
+        }
+
+        validated.sort();
+
+        assert_eq!((0..40).collect::<Vec<usize>>(), validated);
+    }
+
+    #[test]
+    fn test_invalid_splits_are_errors() {
+        let dataset = dataset();
+
+        assert!(Subset::new(&dataset, vec![3, 40]).is_err());
+        assert!(train_validation_test_split(&dataset, 0.6, 0.5, 0).is_err());
+        assert!(train_validation_test_split(&dataset, -0.1, 0.1, 0).is_err());
+        assert!(train_validation_test_split(&dataset, f64::NAN, 0.1, 0).is_err());
+        assert!(matches!(
+            stratified_k_fold(&dataset, 1, 0),
+            Err(NeuraError::InvalidArgument(message))
+                if message == "Cross-validation needs at least 2 folds, got 1"
+        ));
+    }
+}
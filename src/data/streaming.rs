@@ -0,0 +1,203 @@
+use std::{
+    fs::File,
+    io,
+    marker::PhantomData,
+    sync::{mpsc, Arc},
+    thread,
+};
+
+use csv::{Position, StringRecord};
+use nalgebra::DMatrix;
+
+use crate::{core::float::Float, error::NeuraError};
+
+use super::{
+    csv_loader::{check_features_count, CsvError, CsvFormat},
+    dataset::{out_of_range, Batch, Batches, Dataset},
+};
+
+// CSV dataset that keeps only the position of each record in memory and reads
+// the samples from disk when they are requested.
+pub struct StreamingCsvDataset<T: Float = f32> {
+    file_path: String,
+    format: Arc<CsvFormat>,
+    positions: Arc<Vec<Position>>,
+    // Number of batches read ahead by a background thread
+    prefetch: Option<usize>,
+    element: PhantomData<T>,
+}
+
+impl<T: Float> StreamingCsvDataset<T> {
+    // Scans the file once to index the records, failing on the first malformed one
+    pub fn open(
+        file_path: &str,
+        format: CsvFormat,
+        prefetch: Option<usize>,
+    ) -> Result<Self, CsvError> {
+        let mut reader = format.reader(file_path)?;
+        let mut record = StringRecord::new();
+        let mut positions = Vec::new();
+        let mut features_count = None;
+
+        while reader.read_record(&mut record)? {
+            let (input, _) = format.parse_record::<T>(&record)?;
+            // The reader always sets it, but a missing one is an error rather than a panic
+            let position = record.position().cloned().ok_or_else(|| {
+                CsvError::Csv(io::Error::other("A record has no position in the file").into())
+            })?;
+
+            check_features_count(&mut features_count, input.len(), position.line())?;
+
+            positions.push(position);
+        }
+
+        if positions.is_empty() {
+            return Err(CsvError::Empty);
+        }
+
+        Ok(Self {
+            file_path: file_path.to_string(),
+            format: Arc::new(format),
+            positions: Arc::new(positions),
+            prefetch,
+            element: PhantomData,
+        })
+    }
+
+    fn reader(&self, order: Vec<usize>, batch_size: usize) -> Result<BatchReader<T>, NeuraError> {
+        let mut reader = self.format.reader(&self.file_path)?;
+
+        // Seeking does not skip the headers, so they are consumed beforehand
+        if self.format.has_headers {
+            reader.headers().map_err(CsvError::Csv)?;
+        }
+
+        Ok(BatchReader {
+            batch_size: batch_size.max(1),
+            format: Arc::clone(&self.format),
+            next_byte: None,
+            order,
+            positions: Arc::clone(&self.positions),
+            reader,
+            start: 0,
+            element: PhantomData,
+        })
+    }
+}
+
+impl<T: Float> Dataset<T> for StreamingCsvDataset<T> {
+    fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn get(&self, index: usize) -> Result<(DMatrix<T>, DMatrix<T>), NeuraError> {
+        let mut batch = self.reader(vec![index], 1)?.read_batch()?;
+
+        Ok((batch.inputs.remove(0), batch.targets.remove(0)))
+    }
+
+    fn batches_in_order(&self, order: Vec<usize>, batch_size: usize) -> Batches<'_, T> {
+        let reader = match self.reader(order, batch_size) {
+            Ok(reader) => reader,
+            Err(error) => return Box::new(std::iter::once(Err(error))),
+        };
+
+        match self.prefetch {
+            None => Box::new(reader),
+            Some(prefetch) => {
+                let (sender, receiver) = mpsc::sync_channel(prefetch);
+
+                thread::spawn(move || {
+                    for batch in reader {
+                        let failed = batch.is_err();
+
+                        // The receiver was dropped, nobody needs the next batches
+                        if sender.send(batch).is_err() || failed {
+                            break;
+                        }
+                    }
+                });
+
+                Box::new(receiver.into_iter())
+            }
+        }
+    }
+}
+
+struct BatchReader<T: Float> {
+    batch_size: usize,
+    format: Arc<CsvFormat>,
+    // Byte offset the reader is at, to avoid seeking when reading sequentially
+    next_byte: Option<u64>,
+    order: Vec<usize>,
+    positions: Arc<Vec<Position>>,
+    reader: csv::Reader<File>,
+    start: usize,
+    element: PhantomData<T>,
+}
+
+impl<T: Float> BatchReader<T> {
+    fn read_batch(&mut self) -> Result<Batch<T>, NeuraError> {
+        let end = (self.start + self.batch_size).min(self.order.len());
+
+        let mut batch = Batch {
+            inputs: Vec::with_capacity(end - self.start),
+            targets: Vec::with_capacity(end - self.start),
+        };
+
+        let mut record = StringRecord::new();
+
+        for &index in &self.order[self.start..end] {
+            let position = self
+                .positions
+                .get(index)
+                .ok_or_else(|| out_of_range(index, self.positions.len()))?;
+
+            if self.next_byte != Some(position.byte()) {
+                self.reader.seek(position.clone()).map_err(CsvError::Csv)?;
+            }
+
+            // The file changed since it was indexed
+            if !self
+                .reader
+                .read_record(&mut record)
+                .map_err(CsvError::Csv)?
+            {
+                return Err(NeuraError::InvalidArgument(format!(
+                    "Record {} is past the end of the file",
+                    index
+                )));
+            }
+
+            self.next_byte = Some(self.reader.position().byte());
+
+            let (input, target) = self.format.parse_record(&record)?;
+
+            batch.inputs.push(input);
+            batch.targets.push(target);
+        }
+
+        self.start = end;
+
+        Ok(batch)
+    }
+}
+
+impl<T: Float> Iterator for BatchReader<T> {
+    type Item = Result<Batch<T>, NeuraError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.order.len() {
+            return None;
+        }
+
+        let batch = self.read_batch();
+
+        // Nothing is read after an error
+        if batch.is_err() {
+            self.start = self.order.len();
+        }
+
+        Some(batch)
+    }
+}
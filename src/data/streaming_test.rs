@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use nalgebra::DMatrix;
+
+    use crate::{
+        data::{
+            csv_loader::{CsvError, CsvFormat, Features, Normalization},
+            dataset::Dataset,
+            streaming::StreamingCsvDataset,
+        },
+        error::NeuraError,
+    };
+
+    // Label in the first column, features in the others
+    fn format() -> CsvFormat {
+        CsvFormat {
+            label_column: 0,
+            features: Features::AllColumns,
+            has_headers: true,
+            delimiter: b',',
+            normalization: Normalization::None,
+            classes: None,
+            labels: None,
+        }
+    }
+
+    fn write_csv(name: &str, rows: usize) -> String {
+        let path = env::temp_dir().join(format!("neura_rust_{}.csv", name));
+
+        let mut content = "label,a,b\n".to_string();
+
+        for i in 0..rows {
+            content.push_str(&format!("{},{},{}\n", i, i * 2, i * 3));
+        }
+
+        fs::write(&path, content).unwrap();
+
+        path.to_str().unwrap().to_string()
+    }
+
+    fn labels(dataset: &dyn Dataset, shuffle: bool) -> Vec<f32> {
+        dataset
+            .batches(3, shuffle)
+            .map(Result::unwrap)
+            .flat_map(|batch| {
+                batch
+                    .inputs
+                    .iter()
+                    .zip(batch.targets.iter())
+                    .map(|(input, target)| {
+                        assert_eq!(target[0] * 2.0, input[0]);
+                        assert_eq!(target[0] * 3.0, input[1]);
+
+                        target[0]
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_streaming_batches() {
+        let path = write_csv("streaming", 10);
+        let expected: Vec<f32> = (0..10).map(|i| i as f32).collect();
+
+        for prefetch in [None, Some(2)] {
+            let dataset = StreamingCsvDataset::open(&path, format(), prefetch).unwrap();
+
+            assert_eq!(10, dataset.len());
+            assert_eq!(4, dataset.batches_count(3));
+            assert_eq!(expected, labels(&dataset, false));
+
+            let mut shuffled = labels(&dataset, true);
+            shuffled.sort_by(|a, b| a.total_cmp(b));
+
+            assert_eq!(expected, shuffled);
+
+            let (input, target) = dataset.get(7).unwrap();
+
+            assert_eq!(DMatrix::from_vec(2, 1, vec![14.0, 21.0]), input);
+            assert_eq!(DMatrix::from_element(1, 1, 7.0), target);
+        }
+    }
+
+    #[test]
+    fn test_malformed_record_fails_on_open() {
+        let path = env::temp_dir().join("neura_rust_streaming_malformed.csv");
+
+        fs::write(&path, "label,a,b\n0,1,2\n1,x,3\n").unwrap();
+
+        let result = StreamingCsvDataset::<f32>::open(path.to_str().unwrap(), format(), None);
+
+        assert!(matches!(
+            result,
+            Err(CsvError::InvalidNumber {
+                line: 3,
+                column: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_records_changed_after_open_are_errors() {
+        let path = write_csv("streaming_changed", 4);
+
+        for prefetch in [None, Some(2)] {
+            let dataset = StreamingCsvDataset::<f32>::open(&path, format(), prefetch).unwrap();
+
+            // Same length, so the indexed positions still point at records
+            fs::write(&path, "label,a,b\n0,0,0\n1,x,3\n2,4,6\n3,6,9\n").unwrap();
+
+            let batches: Vec<_> = dataset.batches(1, false).collect();
+
+            assert!(batches[0].is_ok());
+            assert!(matches!(
+                batches[1],
+                Err(NeuraError::Csv(CsvError::InvalidNumber { column: 1, .. }))
+            ));
+            assert!(dataset.get(1).is_err());
+            assert!(dataset.get(4).is_err());
+
+            write_csv("streaming_changed", 4);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::{error::Error, fmt, io};
+
+use nalgebra::DMatrix;
+
+use crate::{
+    core::divergence::DivergenceError,
+    data::{array::ReadError, csv_loader::CsvError},
+};
+
+#[derive(Debug)]
+pub enum NeuraError {
+    // A matrix given to a layer, a loss, a metric or the pipeline, with its
+    // expected and actual (rows, columns)
+    ShapeMismatch {
+        context: String,
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    InvalidArgument(String),
+    Divergence(DivergenceError),
+    Csv(CsvError),
+    Read(ReadError),
+    Io(io::Error),
+    // A saved model or file whose content is invalid, like an unknown activation
+    Format(String),
+    // JSON that can't be read or written
+    Serialization(serde_json::Error),
+}
+
+impl NeuraError {
+    pub fn shape_mismatch(context: &str, expected: (usize, usize), found: (usize, usize)) -> Self {
+        NeuraError::ShapeMismatch {
+            context: context.to_string(),
+            expected,
+            found,
+        }
+    }
+}
+
+// Fails unless the matrix has the expected (rows, columns)
+pub fn check_shape<T: fmt::Debug + 'static>(
+    context: &str,
+    expected: (usize, usize),
+    matrix: &DMatrix<T>,
+) -> Result<(), NeuraError> {
+    if matrix.shape() == expected {
+        Ok(())
+    } else {
+        Err(NeuraError::shape_mismatch(
+            context,
+            expected,
+            matrix.shape(),
+        ))
+    }
+}
+
+impl fmt::Display for NeuraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NeuraError::ShapeMismatch {
+                context,
+                expected,
+                found,
+            } => write!(
+                f,
+                "The {} should be {}x{} but is {}x{}",
+                context, expected.0, expected.1, found.0, found.1
+            ),
+            NeuraError::InvalidArgument(message) => write!(f, "{}", message),
+            NeuraError::Divergence(error) => write!(f, "{}", error),
+            NeuraError::Csv(error) => write!(f, "{}", error),
+            NeuraError::Read(error) => write!(f, "{}", error),
+            NeuraError::Io(error) => write!(f, "{}", error),
+            NeuraError::Format(message) => write!(f, "{}", message),
+            NeuraError::Serialization(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for NeuraError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NeuraError::Divergence(error) => Some(error),
+            NeuraError::Csv(error) => Some(error),
+            NeuraError::Read(error) => Some(error),
+            NeuraError::Io(error) => Some(error),
+            NeuraError::Serialization(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<DivergenceError> for NeuraError {
+    fn from(error: DivergenceError) -> Self {
+        NeuraError::Divergence(error)
+    }
+}
+
+impl From<CsvError> for NeuraError {
+    fn from(error: CsvError) -> Self {
+        NeuraError::Csv(error)
+    }
+}
+
+impl From<ReadError> for NeuraError {
+    fn from(error: ReadError) -> Self {
+        NeuraError::Read(error)
+    }
+}
+
+impl From<io::Error> for NeuraError {
+    fn from(error: io::Error) -> Self {
+        NeuraError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for NeuraError {
+    fn from(error: serde_json::Error) -> Self {
+        NeuraError::Serialization(error)
+    }
+}
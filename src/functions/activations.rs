@@ -1,48 +1,62 @@
-use std::f32::consts::E;
-
 use nalgebra::DMatrix;
 
-pub fn sigmoid(raw_output: &DMatrix<f32>) -> DMatrix<f32> {
-    raw_output.map(|x| 1.0 / (1.0 + E.powf(-x)))
+use crate::{autograd::tape::Var, core::float::Float};
+
+pub fn sigmoid<T: Float>(raw_output: &DMatrix<T>) -> DMatrix<T> {
+    raw_output.map(|x| T::one() / (T::one() + (-x).exp()))
 }
 
-pub fn sigmoid_derivative(raw_output: &DMatrix<f32>) -> DMatrix<f32> {
+pub fn sigmoid_derivative<T: Float>(raw_output: &DMatrix<T>) -> DMatrix<T> {
     raw_output.map(|x| {
-        let s = 1.0 / (1.0 + E.powf(-x));
-        s * (1.0 - s)
+        let s = T::one() / (T::one() + (-x).exp());
+        s * (T::one() - s)
     })
 }
 
-pub fn softmax(input: &DMatrix<f32>) -> DMatrix<f32> {
-    let exp_values = input.map(|x| E.powf(x));
+pub fn softmax<T: Float>(input: &DMatrix<T>) -> DMatrix<T> {
+    let exp_values = input.map(|x| x.exp());
     let sum_exp_values = exp_values.sum();
 
     exp_values / (sum_exp_values)
 }
 
-pub fn softmax_derivative(raw_output: &DMatrix<f32>) -> DMatrix<f32> {
-    let n = raw_output.nrows();
-    let m = raw_output.ncols();
+// Unlike the element-wise derivatives, the softmax derivative is its full
+// Jacobian: J[i][j] = s_i * (δ_ij - s_j)
+pub fn softmax_derivative<T: Float>(raw_output: &DMatrix<T>) -> DMatrix<T> {
+    let activated = softmax(raw_output);
 
-    let mut jacobian = DMatrix::<f32>::from_element(n, m, 0.0);
+    DMatrix::from_diagonal(&activated.column(0)) - &activated * activated.transpose()
+}
 
-    for i in 0..n {
-        for j in 0..m {
-            if i == j {
-                jacobian[(i, j)] = raw_output[(i, j)] * (1.0 - raw_output[(i, j)]);
-            } else {
-                jacobian[(i, j)] = -raw_output[(i, j)] * raw_output[(i, j)];
-            }
-        }
-    }
+pub fn relu<T: Float>(raw_output: &DMatrix<T>) -> DMatrix<T> {
+    raw_output.map(|x| if x > T::zero() { x } else { T::zero() })
+}
 
-    jacobian
+pub fn relu_derivative<T: Float>(raw_output: &DMatrix<T>) -> DMatrix<T> {
+    raw_output.map(|x| if x > T::zero() { T::one() } else { T::zero() })
 }
 
-pub fn relu(raw_output: &DMatrix<f32>) -> DMatrix<f32> {
-    raw_output.map(|x| if x > 0.0 { x } else { 0.0 })
+// Activations defined only by their forward pass, differentiated on the tape
+
+pub fn sigmoid_on_tape<T: Float>(raw_output: &Var<T>) -> Var<T> {
+    raw_output.sigmoid()
 }
 
-pub fn relu_derivative(raw_output: &DMatrix<f32>) -> DMatrix<f32> {
-    raw_output.map(|x| if x > 0.0 { 1.0 } else { 0.0 })
+pub fn relu_on_tape<T: Float>(raw_output: &Var<T>) -> Var<T> {
+    raw_output.relu()
+}
+
+pub fn tanh_on_tape<T: Float>(raw_output: &Var<T>) -> Var<T> {
+    raw_output.tanh()
+}
+
+pub fn softmax_on_tape<T: Float>(raw_output: &Var<T>) -> Var<T> {
+    // Shifting by the max changes neither the result nor its gradient, only avoids overflows
+    let max = raw_output.value().max();
+
+    let exp_values = raw_output
+        .sub(&raw_output.constant(DMatrix::from_element(1, 1, max)))
+        .exp();
+
+    exp_values.div(&exp_values.sum())
 }
@@ -1,72 +1,110 @@
 use nalgebra::DMatrix;
 
-pub fn mse(expected: &DMatrix<f32>, predicted: &DMatrix<f32>) -> f32 {
-    let n = expected.shape().1 as f32;
+use crate::{autograd::tape::Var, core::float::Float};
+
+pub fn mse<T: Float>(expected: &DMatrix<T>, predicted: &DMatrix<T>) -> T {
+    let n = T::cast(expected.shape().1 as f64);
 
     let matrix = &(predicted - expected);
-    let map = &matrix.map(|x| x.powf(2.0));
+    let map = &matrix.map(|x| x.powi(2));
     let sum = map.sum();
     sum / n
 }
 
-pub fn mse_derivative(expected: &DMatrix<f32>, predicted: &DMatrix<f32>) -> DMatrix<f32> {
-    predicted - expected
+pub fn mse_derivative<T: Float>(expected: &DMatrix<T>, predicted: &DMatrix<T>) -> DMatrix<T> {
+    let n = T::cast(expected.shape().1 as f64);
+
+    (predicted - expected) * (T::cast(2.0) / n)
 }
 
 // Function to calculate the squared error
-pub fn squared_error(expected: &DMatrix<f32>, predicted: &DMatrix<f32>) -> f32 {
-    (expected - predicted).map(|x| x.powf(2.0) / 2.0).sum()
+pub fn squared_error<T: Float>(expected: &DMatrix<T>, predicted: &DMatrix<T>) -> T {
+    (expected - predicted)
+        .map(|x| x.powi(2) / T::cast(2.0))
+        .sum()
 }
 
 // Function to calculate the derivative of the squared error
-pub fn squared_error_derivative(expected: &DMatrix<f32>, predicted: &DMatrix<f32>) -> DMatrix<f32> {
+pub fn squared_error_derivative<T: Float>(
+    expected: &DMatrix<T>,
+    predicted: &DMatrix<T>,
+) -> DMatrix<T> {
     -(expected - predicted)
 }
 
-pub fn categorical_crossentropy(expected: &DMatrix<f32>, predicted: &DMatrix<f32>) -> f32 {
-    let log_preds = predicted.map(|pred| (pred + 1e-15).ln());
+pub fn categorical_crossentropy<T: Float>(expected: &DMatrix<T>, predicted: &DMatrix<T>) -> T {
+    let log_preds = predicted.map(|pred| (pred + T::cast(1e-15)).ln());
     let product = expected.component_mul(&log_preds);
 
     -product.sum()
 }
 
-pub fn categorical_crossentropy_derivative(
-    expected: &DMatrix<f32>,
-    predicted: &DMatrix<f32>,
-) -> DMatrix<f32> {
-    expected - predicted
-    // -expected.component_div(&predicted.map(|x| if x == 0.0 { 1e-15 } else { x }))
+pub fn categorical_crossentropy_derivative<T: Float>(
+    expected: &DMatrix<T>,
+    predicted: &DMatrix<T>,
+) -> DMatrix<T> {
+    -expected.component_div(&predicted.map(|pred| pred + T::cast(1e-15)))
 }
 
-pub fn binary_crossentropy(y_true: &DMatrix<f32>, y_pred: &DMatrix<f32>) -> f32 {
-    if y_true.shape() != y_pred.shape() {
-        panic!("Shapes of y_true and y_pred must match");
-    }
-
-    let epsilon = 1e-7; // To prevent log(0)
-    let mut loss = 0.0;
+// Loss::evaluate checks the shapes of the target and the prediction before the losses
+pub fn binary_crossentropy<T: Float>(y_true: &DMatrix<T>, y_pred: &DMatrix<T>) -> T {
+    let epsilon = T::cast(1e-7); // To prevent log(0)
+    let mut loss = T::zero();
 
-    for (yt, yp) in y_true.iter().zip(y_pred.iter()) {
-        let yp_clamped = yp.max(epsilon).min(1.0 - epsilon); // Clamping values for stability
-        loss += -yt * yp_clamped.ln() - (1.0 - yt) * (1.0 - yp_clamped).ln();
+    for (&yt, &yp) in y_true.iter().zip(y_pred.iter()) {
+        let yp_clamped = yp.max(epsilon).min(T::one() - epsilon); // Clamping values for stability
+        loss += -yt * yp_clamped.ln() - (T::one() - yt) * (T::one() - yp_clamped).ln();
     }
 
-    loss / (y_true.nrows() * y_true.ncols()) as f32
+    loss / T::cast((y_true.nrows() * y_true.ncols()) as f64)
 }
 
-pub fn binary_crossentropy_derivative(
-    expected: &DMatrix<f32>,
-    predicted: &DMatrix<f32>,
-) -> DMatrix<f32> {
+pub fn binary_crossentropy_derivative<T: Float>(
+    expected: &DMatrix<T>,
+    predicted: &DMatrix<T>,
+) -> DMatrix<T> {
     let mut derivatives = DMatrix::zeros(expected.nrows(), expected.ncols());
 
-    for ((y, y_hat), derivative) in expected
+    for ((&y, &y_hat), derivative) in expected
         .iter()
         .zip(predicted.iter())
         .zip(derivatives.iter_mut())
     {
-        *derivative = -(*y / *y_hat) + ((1.0 - *y) / (1.0 - *y_hat));
+        *derivative = -(y / y_hat) + ((T::one() - y) / (T::one() - y_hat));
     }
 
-    derivatives
+    // The loss is averaged over all the elements
+    derivatives / T::cast((expected.nrows() * expected.ncols()) as f64)
+}
+
+// Losses defined only by their forward pass, differentiated on the tape
+
+pub fn mse_on_tape<T: Float>(expected: &Var<T>, predicted: &Var<T>) -> Var<T> {
+    let n = T::cast(expected.shape().1 as f64);
+
+    predicted.sub(expected).powi(2).sum().scale(T::one() / n)
+}
+
+pub fn squared_error_on_tape<T: Float>(expected: &Var<T>, predicted: &Var<T>) -> Var<T> {
+    expected.sub(predicted).powi(2).sum().scale(T::cast(0.5))
+}
+
+pub fn categorical_crossentropy_on_tape<T: Float>(expected: &Var<T>, predicted: &Var<T>) -> Var<T> {
+    expected
+        .mul(&predicted.add_scalar(T::cast(1e-15)).ln())
+        .sum()
+        .neg()
+}
+
+pub fn binary_crossentropy_on_tape<T: Float>(expected: &Var<T>, predicted: &Var<T>) -> Var<T> {
+    let epsilon = T::cast(1e-7);
+    let clamped = predicted.clamp(epsilon, T::one() - epsilon);
+
+    let positive = expected.mul(&clamped.ln());
+    let negative = expected
+        .neg()
+        .add_scalar(T::one())
+        .mul(&clamped.neg().add_scalar(T::one()).ln());
+
+    positive.add(&negative).mean().neg()
 }
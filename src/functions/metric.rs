@@ -0,0 +1,388 @@
+use nalgebra::DMatrix;
+
+use crate::core::float::Float;
+
+use crate::{
+    data::split::target_class,
+    error::{check_shape, NeuraError},
+};
+
+use super::metrics::{
+    add_to_confusion_matrix, all_labels_right, average_precision_of_counts, balanced_accuracy,
+    cohen_kappa, get_class_confusion_matrices, in_top_k, label_predicted, matthews_correlation,
+    roc_auc_of_counts, sample_log_loss, ClassConfusionMatrix,
+};
+
+// Score accumulated over the samples given to update since the last reset
+pub trait Metric<T: Float = f32> {
+    fn name(&self) -> String;
+    // Fails on a prediction the metric can't score, leaving the metric as it was
+    fn update(&mut self, prediction: &DMatrix<T>, target: &DMatrix<T>) -> Result<(), NeuraError>;
+    fn result(&self) -> f32;
+    fn reset(&mut self);
+
+    fn format(&self, score: f32) -> String {
+        format!("{:.0}%", score * 100.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricKind {
+    Accuracy,
+    // Macro averages over the classes
+    Precision,
+    Recall,
+    F1Score,
+    TopKAccuracy(usize),
+    BalancedAccuracy,
+    MatthewsCorrelation,
+    CohenKappa,
+    // One-vs-rest macro averages over the classes. The scores are counted in 1000
+    // bins of width 0.001 instead of being kept, so the scores sharing a bin count
+    // as ties. The ROC AUC is then off by at most half the share of the
+    // (positive, negative) pairs in a same bin, and both are usually within 1e-3
+    // of roc_auc and average_precision.
+    RocAuc,
+    PrAuc,
+    LogLoss,
+    MeanAbsoluteError,
+    RootMeanSquaredError,
+    R2Score,
+    MeanAbsolutePercentageError,
+    // Multi-label metrics, the labels are predicted from the threshold
+    HammingLoss(f32),
+    SubsetAccuracy(f32),
+    // F1 score of the label with the threshold
+    LabelF1(usize, f32),
+}
+
+impl MetricKind {
+    pub fn name(&self) -> String {
+        match self {
+            MetricKind::Accuracy => "accuracy".to_string(),
+            MetricKind::Precision => "precision".to_string(),
+            MetricKind::Recall => "recall".to_string(),
+            MetricKind::F1Score => "f1-score".to_string(),
+            MetricKind::TopKAccuracy(k) => format!("top-{}-accuracy", k),
+            MetricKind::BalancedAccuracy => "balanced-accuracy".to_string(),
+            MetricKind::MatthewsCorrelation => "mcc".to_string(),
+            MetricKind::CohenKappa => "cohen-kappa".to_string(),
+            MetricKind::RocAuc => "roc-auc".to_string(),
+            MetricKind::PrAuc => "pr-auc".to_string(),
+            MetricKind::LogLoss => "log-loss".to_string(),
+            MetricKind::MeanAbsoluteError => "mae".to_string(),
+            MetricKind::RootMeanSquaredError => "rmse".to_string(),
+            MetricKind::R2Score => "r2".to_string(),
+            MetricKind::MeanAbsolutePercentageError => "mape".to_string(),
+            MetricKind::HammingLoss(threshold) => format!("hamming-loss@{}", threshold),
+            MetricKind::SubsetAccuracy(threshold) => format!("subset-accuracy@{}", threshold),
+            MetricKind::LabelF1(label, threshold) => format!("label-f1[{}]@{}", label, threshold),
+        }
+    }
+
+    // Whether the score is a fraction printed as a percentage
+    pub fn is_percentage(&self) -> bool {
+        !matches!(
+            self,
+            MetricKind::MatthewsCorrelation
+                | MetricKind::CohenKappa
+                | MetricKind::LogLoss
+                | MetricKind::MeanAbsoluteError
+                | MetricKind::RootMeanSquaredError
+                | MetricKind::R2Score
+        )
+    }
+
+    // Score of the samples at once
+    pub fn score<T: Float>(
+        &self,
+        predictions: &[DMatrix<T>],
+        targets: &[DMatrix<T>],
+    ) -> Result<f32, NeuraError> {
+        if predictions.len() != targets.len() {
+            return Err(NeuraError::InvalidArgument(format!(
+                "Got {} predictions for {} targets",
+                predictions.len(),
+                targets.len()
+            )));
+        }
+
+        let mut metric = BuiltinMetric::new(*self);
+
+        for (prediction, target) in predictions.iter().zip(targets.iter()) {
+            Metric::<T>::update(&mut metric, prediction, target)?;
+        }
+
+        Ok(Metric::<T>::result(&metric))
+    }
+}
+
+// Bins of the scores from 0 to 1 of the ranking metrics, scores out of the range
+// fall in the first or last bin
+const SCORE_BINS: usize = 1000;
+
+// What a built-in metric keeps of the samples, which doesn't grow with their amount
+#[derive(Debug, Clone, PartialEq)]
+enum State {
+    ConfusionMatrix(DMatrix<usize>),
+    // Sum and count of the values averaged
+    Mean(f64, usize),
+    Label(ClassConfusionMatrix),
+    // Count, mean and squared deviations of the targets of every output (Welford),
+    // and the squared residuals
+    Outputs(Vec<(usize, f64, f64, f64)>),
+    // (positives, negatives) in every score bin, for every class
+    ScoreBins(Vec<Vec<(usize, usize)>>),
+}
+
+// Built-in metric of a kind, updated one sample at a time
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinMetric {
+    kind: MetricKind,
+    state: State,
+}
+
+impl BuiltinMetric {
+    pub fn new(kind: MetricKind) -> Self {
+        Self {
+            kind,
+            state: Self::empty_state(kind),
+        }
+    }
+
+    pub fn get_kind(&self) -> MetricKind {
+        self.kind
+    }
+
+    fn empty_state(kind: MetricKind) -> State {
+        match kind {
+            MetricKind::Accuracy
+            | MetricKind::Precision
+            | MetricKind::Recall
+            | MetricKind::F1Score
+            | MetricKind::BalancedAccuracy
+            | MetricKind::MatthewsCorrelation
+            | MetricKind::CohenKappa => State::ConfusionMatrix(DMatrix::zeros(0, 0)),
+            MetricKind::LabelF1(_, _) => State::Label(ClassConfusionMatrix::empty()),
+            MetricKind::R2Score => State::Outputs(Vec::new()),
+            MetricKind::RocAuc | MetricKind::PrAuc => State::ScoreBins(Vec::new()),
+            _ => State::Mean(0.0, 0),
+        }
+    }
+}
+
+impl<T: Float> Metric<T> for BuiltinMetric {
+    fn name(&self) -> String {
+        self.kind.name()
+    }
+
+    fn update(&mut self, prediction: &DMatrix<T>, target: &DMatrix<T>) -> Result<(), NeuraError> {
+        check_shape("prediction", target.shape(), prediction)?;
+
+        let errors = || {
+            prediction
+                .iter()
+                .zip(target.iter())
+                .map(|(p, t)| (p.as_f64(), t.as_f64()))
+        };
+
+        match &mut self.state {
+            State::ConfusionMatrix(confusion_matrix) => {
+                add_to_confusion_matrix(confusion_matrix, prediction, target)?
+            }
+            State::Label(matrix) => {
+                if let MetricKind::LabelF1(label, threshold) = self.kind {
+                    if label >= prediction.len() {
+                        return Err(NeuraError::InvalidArgument(format!(
+                            "The label {} is out of the {} outputs",
+                            label,
+                            prediction.len()
+                        )));
+                    }
+
+                    matrix.count(
+                        label_predicted(prediction[label], threshold),
+                        label_predicted(target[label], 0.5),
+                    );
+                }
+            }
+            State::Outputs(outputs) => {
+                outputs.resize(prediction.len().max(outputs.len()), (0, 0.0, 0.0, 0.0));
+
+                for ((count, mean, squared_deviations, residuals), (predicted, expected)) in
+                    outputs.iter_mut().zip(errors())
+                {
+                    *count += 1;
+
+                    let delta = expected - *mean;
+
+                    *mean += delta / *count as f64;
+                    *squared_deviations += delta * (expected - *mean);
+                    *residuals += (expected - predicted).powi(2);
+                }
+            }
+            State::ScoreBins(classes) => {
+                let bin = |score: T| {
+                    ((score.as_f64() * SCORE_BINS as f64).max(0.0) as usize).min(SCORE_BINS - 1)
+                };
+
+                let class = target_class(target);
+
+                // Single outputs are the scores of the positive class of binary problems
+                if prediction.len() == 1 {
+                    classes.resize(1, vec![(0, 0); SCORE_BINS]);
+
+                    let counts = &mut classes[0][bin(prediction[0])];
+
+                    if class == 1 {
+                        counts.0 += 1;
+                    } else {
+                        counts.1 += 1;
+                    }
+                } else {
+                    classes.resize(
+                        prediction.len().max(classes.len()),
+                        vec![(0, 0); SCORE_BINS],
+                    );
+
+                    for (i, (bins, &score)) in classes.iter_mut().zip(prediction.iter()).enumerate()
+                    {
+                        if i == class {
+                            bins[bin(score)].0 += 1;
+                        } else {
+                            bins[bin(score)].1 += 1;
+                        }
+                    }
+                }
+            }
+            State::Mean(sum, count) => {
+                let (value, values) = match self.kind {
+                    MetricKind::TopKAccuracy(k) => {
+                        (in_top_k(prediction, target, k) as u8 as f64, 1)
+                    }
+                    MetricKind::SubsetAccuracy(threshold) => (
+                        all_labels_right(prediction, target, threshold) as u8 as f64,
+                        1,
+                    ),
+                    MetricKind::LogLoss => (sample_log_loss(prediction, target), 1),
+                    MetricKind::MeanAbsoluteError => {
+                        (errors().map(|(p, t)| (p - t).abs()).sum(), prediction.len())
+                    }
+                    MetricKind::RootMeanSquaredError => (
+                        errors().map(|(p, t)| (p - t).powi(2)).sum(),
+                        prediction.len(),
+                    ),
+                    MetricKind::MeanAbsolutePercentageError => (
+                        errors()
+                            .map(|(p, t)| (p - t).abs() / t.abs().max(f64::EPSILON))
+                            .sum(),
+                        prediction.len(),
+                    ),
+                    MetricKind::HammingLoss(threshold) => (
+                        errors()
+                            .filter(|&(p, t)| (p >= threshold as f64) != (t >= 0.5))
+                            .count() as f64,
+                        prediction.len(),
+                    ),
+                    _ => (0.0, 0),
+                };
+
+                *sum += value;
+                *count += values;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn result(&self) -> f32 {
+        match &self.state {
+            State::ConfusionMatrix(confusion_matrix) => {
+                if confusion_matrix.is_empty() {
+                    return 0.0;
+                }
+
+                let macro_average = |score: fn(&ClassConfusionMatrix) -> f32| {
+                    let class_confusion_matrices = get_class_confusion_matrices(confusion_matrix);
+
+                    class_confusion_matrices.iter().map(score).sum::<f32>()
+                        / class_confusion_matrices.len() as f32
+                };
+
+                match self.kind {
+                    MetricKind::Precision => macro_average(ClassConfusionMatrix::precision),
+                    MetricKind::Recall => macro_average(ClassConfusionMatrix::recall),
+                    MetricKind::F1Score => macro_average(ClassConfusionMatrix::f1_score),
+                    MetricKind::BalancedAccuracy => balanced_accuracy(confusion_matrix),
+                    MetricKind::MatthewsCorrelation => matthews_correlation(confusion_matrix),
+                    MetricKind::CohenKappa => cohen_kappa(confusion_matrix),
+                    _ => confusion_matrix.trace() as f32 / confusion_matrix.sum().max(1) as f32,
+                }
+            }
+            State::Label(matrix) => matrix.f1_score(),
+            State::Outputs(outputs) => {
+                let scores = outputs.iter().map(|&(_, _, total, residuals)| {
+                    if total == 0.0 {
+                        (residuals == 0.0) as u8 as f64
+                    } else {
+                        1.0 - residuals / total
+                    }
+                });
+
+                (scores.sum::<f64>() / outputs.len().max(1) as f64) as f32
+            }
+            State::ScoreBins(classes) => {
+                let score = match self.kind {
+                    MetricKind::PrAuc => average_precision_of_counts,
+                    _ => roc_auc_of_counts,
+                };
+
+                // Classes absent from the targets are skipped
+                let scores: Vec<f32> = classes
+                    .iter()
+                    .filter(|bins| bins.iter().any(|(positives, _)| *positives > 0))
+                    .map(|bins| score(bins))
+                    .collect();
+
+                scores.iter().sum::<f32>() / scores.len().max(1) as f32
+            }
+            State::Mean(sum, count) => {
+                let mean = if *count == 0 {
+                    0.0
+                } else {
+                    sum / *count as f64
+                };
+
+                match self.kind {
+                    MetricKind::RootMeanSquaredError => mean.sqrt() as f32,
+                    _ => mean as f32,
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = Self::empty_state(self.kind);
+    }
+
+    fn format(&self, score: f32) -> String {
+        if self.kind.is_percentage() {
+            format!("{:.0}%", score * 100.0)
+        } else {
+            format!("{:.4}", score)
+        }
+    }
+}
+
+impl<T: Float> From<MetricKind> for Box<dyn Metric<T>> {
+    fn from(kind: MetricKind) -> Self {
+        Box::new(BuiltinMetric::new(kind))
+    }
+}
+
+// Prints the result of every metric after the loss
+pub fn print_metrics<T: Float>(metrics: &[Box<dyn Metric<T>>]) {
+    metrics
+        .iter()
+        .for_each(|metric| print!(" {}: {}", metric.name(), metric.format(metric.result())))
+}
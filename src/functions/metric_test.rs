@@ -0,0 +1,222 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        core::{layer::Layer, model::Model},
+        data::dataset::InMemoryDataset,
+        error::NeuraError,
+        functions::{
+            activations::{softmax, softmax_derivative},
+            losses::{categorical_crossentropy, categorical_crossentropy_derivative},
+            metric::{BuiltinMetric, Metric, MetricKind},
+            metrics::{average_precision, log_loss, r2_score, roc_auc, root_mean_squared_error},
+            registry::metric_by_name,
+        },
+    };
+
+    // Counts the samples it was updated with
+    struct SampleCount(usize);
+
+    impl Metric for SampleCount {
+        fn name(&self) -> String {
+            "samples".to_string()
+        }
+
+        fn update(
+            &mut self,
+            _prediction: &DMatrix<f32>,
+            _target: &DMatrix<f32>,
+        ) -> Result<(), NeuraError> {
+            self.0 += 1;
+
+            Ok(())
+        }
+
+        fn result(&self) -> f32 {
+            self.0 as f32
+        }
+
+        fn reset(&mut self) {
+            self.0 = 0;
+        }
+    }
+
+    #[test]
+    fn test_custom_and_builtin_metrics() {
+        let model = Model::new(
+            vec![Layer::new(softmax, softmax_derivative, 2, 2)],
+            categorical_crossentropy,
+            categorical_crossentropy_derivative,
+        );
+
+        let dataset = InMemoryDataset::new(
+            vec![DMatrix::from_vec(2, 1, vec![1.0, 0.0]); 3],
+            vec![DMatrix::from_vec(2, 1, vec![1.0, 0.0]); 3],
+        )
+        .unwrap();
+
+        let scores = model
+            .test(
+                vec![Box::new(SampleCount(7)), MetricKind::TopKAccuracy(2).into()],
+                &dataset,
+            )
+            .unwrap();
+
+        assert_eq!(
+            vec!["loss", "samples", "top-2-accuracy"],
+            scores
+                .iter()
+                .map(|(metric, _)| metric.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!(3.0, scores[1].1);
+        assert_eq!(1.0, scores[2].1);
+    }
+
+    #[test]
+    fn test_builtin_metrics_accumulate_like_the_batch_functions() {
+        let predictions: Vec<DMatrix<f32>> = [[0.1, 0.9], [0.4, 0.6], [0.35, 0.65], [0.8, 0.2]]
+            .iter()
+            .map(|scores| DMatrix::from_column_slice(2, 1, scores))
+            .collect();
+        let targets: Vec<DMatrix<f32>> = [[0.0, 1.0], [0.0, 1.0], [1.0, 0.0], [1.0, 0.0]]
+            .iter()
+            .map(|target| DMatrix::from_column_slice(2, 1, target))
+            .collect();
+
+        let scores_of_class_0: Vec<(f64, bool)> = predictions
+            .iter()
+            .zip(targets.iter())
+            .map(|(prediction, target)| (prediction[0] as f64, target[0] == 1.0))
+            .collect();
+
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+
+        assert!(close(
+            root_mean_squared_error(&predictions, &targets),
+            MetricKind::RootMeanSquaredError
+                .score(&predictions, &targets)
+                .unwrap()
+        ));
+        assert!(close(
+            r2_score(&predictions, &targets),
+            MetricKind::R2Score.score(&predictions, &targets).unwrap()
+        ));
+        assert!(close(
+            log_loss(&predictions, &targets),
+            MetricKind::LogLoss.score(&predictions, &targets).unwrap()
+        ));
+        // Both classes rank the samples the same way
+        assert!(close(
+            roc_auc(&scores_of_class_0),
+            MetricKind::RocAuc.score(&predictions, &targets).unwrap()
+        ));
+        assert!(close(
+            average_precision(&scores_of_class_0),
+            MetricKind::PrAuc.score(&predictions, &targets).unwrap()
+        ));
+
+        let mut accuracy = BuiltinMetric::new(MetricKind::Accuracy);
+
+        predictions
+            .iter()
+            .zip(targets.iter())
+            .for_each(|(prediction, target)| accuracy.update(prediction, target).unwrap());
+
+        assert_eq!(0.75, Metric::<f32>::result(&accuracy));
+
+        Metric::<f32>::reset(&mut accuracy);
+
+        assert_eq!(BuiltinMetric::new(MetricKind::Accuracy), accuracy);
+    }
+
+    #[test]
+    fn test_binned_ranking_metrics_are_close_to_the_exact_ones() {
+        // Scores spread over [0, 1] by a linear congruential generator, with more
+        // positives among the high ones, so many of them share a score bin
+        let mut state = 12345_u64;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+
+            (state >> 11) as f64 / (1_u64 << 53) as f64
+        };
+
+        let scores: Vec<(f64, bool)> = (0..5000)
+            .map(|_| {
+                let score = next();
+
+                (score, next() < score)
+            })
+            .collect();
+
+        let predictions: Vec<DMatrix<f32>> = scores
+            .iter()
+            .map(|(score, _)| DMatrix::from_element(1, 1, *score as f32))
+            .collect();
+        let targets: Vec<DMatrix<f32>> = scores
+            .iter()
+            .map(|(_, positive)| DMatrix::from_element(1, 1, *positive as u8 as f32))
+            .collect();
+
+        // The documented bound of the 1000 score bins
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+
+        let exact = roc_auc(&scores);
+        let binned = MetricKind::RocAuc.score(&predictions, &targets).unwrap();
+
+        assert!(close(exact, binned), "exact {} binned {}", exact, binned);
+
+        let exact = average_precision(&scores);
+        let binned = MetricKind::PrAuc.score(&predictions, &targets).unwrap();
+
+        assert!(close(exact, binned), "exact {} binned {}", exact, binned);
+    }
+
+    #[test]
+    fn test_builtin_metrics_reject_mismatched_shapes() {
+        let mut accuracy = BuiltinMetric::new(MetricKind::Accuracy);
+
+        let result = accuracy.update(
+            &DMatrix::from_vec(3, 1, vec![0.1, 0.2, 0.7]),
+            &DMatrix::from_vec(2, 1, vec![0.0, 1.0]),
+        );
+
+        assert!(matches!(result, Err(NeuraError::ShapeMismatch { .. })));
+        assert!(MetricKind::Accuracy
+            .score::<f32>(&[DMatrix::zeros(2, 1)], &[])
+            .is_err());
+    }
+
+    #[test]
+    fn test_label_out_of_the_outputs_is_an_error() {
+        let mut label_f1 = BuiltinMetric::new(MetricKind::LabelF1(3, 0.5));
+
+        let result = label_f1.update(
+            &DMatrix::from_vec(2, 1, vec![0.9_f32, 0.1]),
+            &DMatrix::from_vec(2, 1, vec![1.0, 0.0]),
+        );
+
+        assert!(matches!(result, Err(NeuraError::InvalidArgument(_))));
+        assert_eq!(BuiltinMetric::new(MetricKind::LabelF1(3, 0.5)), label_f1);
+    }
+
+    #[test]
+    fn test_metric_names_round_trip() {
+        for kind in [
+            MetricKind::Accuracy,
+            MetricKind::TopKAccuracy(3),
+            MetricKind::CohenKappa,
+            MetricKind::R2Score,
+            MetricKind::HammingLoss(0.5),
+            MetricKind::SubsetAccuracy(0.25),
+            MetricKind::LabelF1(2, 0.5),
+        ] {
+            assert_eq!(Some(kind), metric_by_name(&kind.name()));
+        }
+
+        assert_eq!(None, metric_by_name("top-0-accuracy"));
+        assert_eq!(None, metric_by_name("label-f1[x]@0.5"));
+        assert_eq!(None, metric_by_name("auc"));
+    }
+}
@@ -1,5 +1,11 @@
 use nalgebra::DMatrix;
 
+use crate::{
+    core::float::Float,
+    data::split::target_class,
+    error::{check_shape, NeuraError},
+};
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ClassConfusionMatrix {
     pub false_negatives: usize,
@@ -39,6 +45,16 @@ impl ClassConfusionMatrix {
         }
     }
 
+    // Counts a binary prediction of the class
+    pub fn count(&mut self, predicted: bool, actual: bool) {
+        match (predicted, actual) {
+            (true, true) => self.true_positives += 1,
+            (true, false) => self.false_positives += 1,
+            (false, true) => self.false_negatives += 1,
+            (false, false) => self.true_negatives += 1,
+        }
+    }
+
     pub fn precision(&self) -> f32 {
         if self.true_positives + self.false_positives == 0 {
             0.0
@@ -65,102 +81,428 @@ impl ClassConfusionMatrix {
             2.0 * (precision * recall) / (precision + recall)
         }
     }
+}
 
-    pub fn clone(&self) -> Self {
-        Self {
-            false_negatives: self.false_negatives,
-            false_positives: self.false_positives,
-            true_negatives: self.true_negatives,
-            true_positives: self.true_positives,
+// Targets and predictions of a single value are binary problems of 2 classes
+pub fn calculate_confusion_matrix<T: Float>(
+    predictions: &[DMatrix<T>],
+    targets: &[DMatrix<T>],
+) -> Result<DMatrix<usize>, NeuraError> {
+    let mut confusion_matrix = DMatrix::zeros(0, 0);
+
+    for (act_matrix, exp_matrix) in predictions.iter().zip(targets.iter()) {
+        add_to_confusion_matrix(&mut confusion_matrix, act_matrix, exp_matrix)?;
+    }
+
+    Ok(confusion_matrix)
+}
+
+// Counts one sample, sizing an empty confusion matrix from the prediction. Fails
+// on a predicted or target class the matrix has no row for, leaving it as it was.
+pub fn add_to_confusion_matrix<T: Float>(
+    confusion_matrix: &mut DMatrix<usize>,
+    prediction: &DMatrix<T>,
+    target: &DMatrix<T>,
+) -> Result<(), NeuraError> {
+    let num_classes = if confusion_matrix.is_empty() {
+        prediction.nrows().max(2)
+    } else {
+        confusion_matrix.nrows()
+    };
+
+    let predicted_class = determine_predicted_class(prediction);
+    let exp_class = target_class(target);
+    let class = predicted_class.max(exp_class);
+
+    if class >= num_classes {
+        return Err(NeuraError::shape_mismatch(
+            "confusion matrix",
+            (class + 1, class + 1),
+            (num_classes, num_classes),
+        ));
+    }
+
+    if confusion_matrix.is_empty() {
+        *confusion_matrix = DMatrix::zeros(num_classes, num_classes);
+    }
+
+    confusion_matrix[(exp_class, predicted_class)] += 1;
+
+    Ok(())
+}
+
+fn determine_predicted_class<T: Float>(matrix: &DMatrix<T>) -> usize {
+    if matrix.len() == 1 {
+        return (matrix[0] >= T::cast(0.5)) as usize;
+    }
+
+    target_class(matrix)
+}
+
+// Absolute and squared errors are averaged over every output of every sample
+pub fn mean_absolute_error<T: Float>(predictions: &[DMatrix<T>], targets: &[DMatrix<T>]) -> f32 {
+    mean_over_outputs(predictions, targets, |predicted, expected| {
+        (predicted - expected).abs()
+    })
+}
+
+pub fn root_mean_squared_error<T: Float>(
+    predictions: &[DMatrix<T>],
+    targets: &[DMatrix<T>],
+) -> f32 {
+    mean_over_outputs(predictions, targets, |predicted, expected| {
+        (predicted - expected).powi(2)
+    })
+    .sqrt()
+}
+
+// Relative to the targets, which are taken as at least f64::EPSILON in magnitude
+pub fn mean_absolute_percentage_error<T: Float>(
+    predictions: &[DMatrix<T>],
+    targets: &[DMatrix<T>],
+) -> f32 {
+    mean_over_outputs(predictions, targets, |predicted, expected| {
+        (predicted - expected).abs() / expected.abs().max(f64::EPSILON)
+    })
+}
+
+// Coefficient of determination of each output, averaged over the outputs. An output
+// with constant targets scores 1 if predicted exactly and 0 otherwise.
+pub fn r2_score<T: Float>(predictions: &[DMatrix<T>], targets: &[DMatrix<T>]) -> f32 {
+    let Some(outputs) = targets.first().map(|target| target.len()) else {
+        return 0.0;
+    };
+
+    let scores = (0..outputs).map(|output| {
+        let mean = targets
+            .iter()
+            .map(|target| target[output].as_f64())
+            .sum::<f64>()
+            / targets.len() as f64;
+
+        let (residual, total) = predictions.iter().zip(targets.iter()).fold(
+            (0.0, 0.0),
+            |(residual, total), (predicted, expected)| {
+                let expected = expected[output].as_f64();
+
+                (
+                    residual + (expected - predicted[output].as_f64()).powi(2),
+                    total + (expected - mean).powi(2),
+                )
+            },
+        );
+
+        if total == 0.0 {
+            (residual == 0.0) as u8 as f64
+        } else {
+            1.0 - residual / total
         }
+    });
+
+    (scores.sum::<f64>() / outputs as f64) as f32
+}
+
+fn mean_over_outputs<T: Float>(
+    predictions: &[DMatrix<T>],
+    targets: &[DMatrix<T>],
+    error: impl Fn(f64, f64) -> f64,
+) -> f32 {
+    let (sum, count) = predictions
+        .iter()
+        .zip(targets.iter())
+        .flat_map(|(predicted, expected)| predicted.iter().zip(expected.iter()))
+        .fold((0.0, 0), |(sum, count), (predicted, expected)| {
+            (
+                sum + error(predicted.as_f64(), expected.as_f64()),
+                count + 1,
+            )
+        });
+
+    if count == 0 {
+        0.0
+    } else {
+        (sum / count as f64) as f32
     }
 }
 
-pub fn calculate_confusion_matrix(
-    predictions: &Vec<DMatrix<f32>>,
-    targets: &Vec<DMatrix<f32>>,
-) -> DMatrix<usize> {
-    let num_classes = predictions.first().unwrap().nrows();
+// Multi-label outputs are independent 0/1 labels, predicted when they reach the threshold.
+// The targets are positive from 0.5.
+pub fn label_predicted<T: Float>(value: T, threshold: f32) -> bool {
+    value.as_f64() >= threshold as f64
+}
 
-    let mut confusion_matrix = DMatrix::zeros(num_classes, num_classes);
+// Fraction of the labels of every sample predicted wrong
+pub fn hamming_loss<T: Float>(
+    predictions: &[DMatrix<T>],
+    targets: &[DMatrix<T>],
+    threshold: f32,
+) -> f32 {
+    mean_over_outputs(predictions, targets, |predicted, expected| {
+        ((predicted >= threshold as f64) != (expected >= 0.5)) as u8 as f64
+    })
+}
 
-    for (act_matrix, exp_matrix) in predictions.iter().zip(targets.iter()) {
-        let predicted_class = determine_predicted_class(act_matrix);
-        let exp_class = determine_actual_class(exp_matrix);
+// Fraction of the samples with all their labels predicted right
+pub fn subset_accuracy<T: Float>(
+    predictions: &[DMatrix<T>],
+    targets: &[DMatrix<T>],
+    threshold: f32,
+) -> f32 {
+    let correct = predictions
+        .iter()
+        .zip(targets.iter())
+        .filter(|(predicted, expected)| all_labels_right(predicted, expected, threshold))
+        .count();
+
+    if predictions.is_empty() {
+        0.0
+    } else {
+        correct as f32 / predictions.len() as f32
+    }
+}
+
+pub fn all_labels_right<T: Float>(
+    prediction: &DMatrix<T>,
+    target: &DMatrix<T>,
+    threshold: f32,
+) -> bool {
+    prediction
+        .iter()
+        .zip(target.iter())
+        .all(|(&predicted, &expected)| {
+            label_predicted(predicted, threshold) == label_predicted(expected, 0.5)
+        })
+}
+
+// Confusion matrix of each label, with a threshold per label
+pub fn label_confusion_matrices<T: Float>(
+    predictions: &[DMatrix<T>],
+    targets: &[DMatrix<T>],
+    thresholds: &[f32],
+) -> Result<Vec<ClassConfusionMatrix>, NeuraError> {
+    let mut matrices = vec![ClassConfusionMatrix::empty(); thresholds.len()];
+
+    for (predicted, expected) in predictions.iter().zip(targets.iter()) {
+        check_shape("prediction", (thresholds.len(), 1), predicted)?;
+        check_shape("target", predicted.shape(), expected)?;
+
+        for (label, matrix) in matrices.iter_mut().enumerate() {
+            matrix.count(
+                label_predicted(predicted[label], thresholds[label]),
+                label_predicted(expected[label], 0.5),
+            );
+        }
+    }
+
+    Ok(matrices)
+}
+
+pub fn label_f1_scores<T: Float>(
+    predictions: &[DMatrix<T>],
+    targets: &[DMatrix<T>],
+    thresholds: &[f32],
+) -> Result<Vec<f32>, NeuraError> {
+    Ok(label_confusion_matrices(predictions, targets, thresholds)?
+        .iter()
+        .map(ClassConfusionMatrix::f1_score)
+        .collect())
+}
+
+// Fraction of the samples whose class is among the k highest scores
+pub fn top_k_accuracy<T: Float>(
+    predictions: &[DMatrix<T>],
+    targets: &[DMatrix<T>],
+    k: usize,
+) -> f32 {
+    let correct = predictions
+        .iter()
+        .zip(targets.iter())
+        .filter(|(predicted, expected)| in_top_k(predicted, expected, k))
+        .count();
 
-        confusion_matrix[(exp_class, predicted_class)] += 1;
+    correct as f32 / predictions.len().max(1) as f32
+}
+
+pub fn in_top_k<T: Float>(prediction: &DMatrix<T>, target: &DMatrix<T>, k: usize) -> bool {
+    let class = target_class(target);
+
+    if prediction.len() == 1 {
+        return determine_predicted_class(prediction) == class || k > 1;
     }
 
-    confusion_matrix
+    let score = prediction
+        .get(class)
+        .map_or(f64::NEG_INFINITY, |score| score.as_f64());
+
+    prediction
+        .iter()
+        .filter(|other| other.as_f64() > score)
+        .count()
+        < k
 }
 
-fn determine_predicted_class(matrix: &DMatrix<f32>) -> usize {
-    matrix
+// Mean recall of the classes present in the targets
+pub fn balanced_accuracy(confusion_matrix: &DMatrix<usize>) -> f32 {
+    let recalls: Vec<f32> = get_class_confusion_matrices(confusion_matrix)
         .iter()
-        .enumerate()
-        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-        .unwrap()
-        .0
+        .filter(|matrix| matrix.true_positives + matrix.false_negatives > 0)
+        .map(ClassConfusionMatrix::recall)
+        .collect();
+
+    recalls.iter().sum::<f32>() / recalls.len().max(1) as f32
+}
+
+// Multiclass Matthews correlation coefficient, from -1 to 1
+pub fn matthews_correlation(confusion_matrix: &DMatrix<usize>) -> f32 {
+    let samples = confusion_matrix.sum() as f64;
+    let correct = confusion_matrix.trace() as f64;
+
+    let predicted = confusion_matrix.row_sum().map(|count| count as f64);
+    let actual = confusion_matrix.column_sum().map(|count| count as f64);
+
+    let numerator = correct * samples - predicted.dot(&actual.transpose());
+    let denominator = ((samples * samples - predicted.norm_squared())
+        * (samples * samples - actual.norm_squared()))
+    .sqrt();
+
+    if denominator == 0.0 {
+        0.0
+    } else {
+        (numerator / denominator) as f32
+    }
+}
+
+// Agreement between predictions and targets beyond chance, from -1 to 1
+pub fn cohen_kappa(confusion_matrix: &DMatrix<usize>) -> f32 {
+    let samples = confusion_matrix.sum() as f64;
+
+    if samples == 0.0 {
+        return 0.0;
+    }
+
+    let observed = confusion_matrix.trace() as f64 / samples;
+    let expected = (0..confusion_matrix.nrows())
+        .map(|class| {
+            confusion_matrix.row(class).sum() as f64 * confusion_matrix.column(class).sum() as f64
+        })
+        .sum::<f64>()
+        / (samples * samples);
+
+    if expected == 1.0 {
+        0.0
+    } else {
+        ((observed - expected) / (1.0 - expected)) as f32
+    }
 }
 
-fn determine_actual_class(matrix: &DMatrix<f32>) -> usize {
-    matrix
+// Cross-entropy of the scores, clipped away from 0 and 1. Single outputs are
+// the probabilities of the positive class.
+pub fn log_loss<T: Float>(predictions: &[DMatrix<T>], targets: &[DMatrix<T>]) -> f32 {
+    let loss: f64 = predictions
         .iter()
-        .enumerate()
-        .find(|&(_, &value)| value == 1.0)
-        .unwrap()
-        .0
+        .zip(targets.iter())
+        .map(|(predicted, expected)| sample_log_loss(predicted, expected))
+        .sum();
+
+    (loss / predictions.len().max(1) as f64) as f32
 }
 
-pub fn print_metrics(
-    epoch_predictions: Vec<DMatrix<f32>>,
-    metrics: &Vec<String>,
-    y: &Vec<DMatrix<f32>>,
-) {
-    let confusion_matrix = calculate_confusion_matrix(&epoch_predictions, y);
+pub fn sample_log_loss<T: Float>(prediction: &DMatrix<T>, target: &DMatrix<T>) -> f64 {
+    let clip = |score: T| score.as_f64().clamp(1e-15, 1.0 - 1e-15);
+
+    if prediction.len() == 1 {
+        let (score, target) = (clip(prediction[0]), target[0].as_f64());
 
-    // println!("Confusion matrix: {}", confusion_matrix);
+        -(target * score.ln() + (1.0 - target) * (1.0 - score).ln())
+    } else {
+        -prediction
+            .iter()
+            .zip(target.iter())
+            .map(|(&score, &target)| target.as_f64() * clip(score).ln())
+            .sum::<f64>()
+    }
+}
 
-    let class_confusion_matrices = get_class_confusion_matrices(&confusion_matrix);
+// (positives, negatives) of every distinct score, in ascending order of the scores
+fn score_counts(scores: &[(f64, bool)]) -> Vec<(usize, usize)> {
+    let mut scores = scores.to_vec();
 
-    metrics.iter().for_each(|metric| {
-        let mut score: f32 = 0.0;
+    scores.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut counts: Vec<(usize, usize)> = Vec::new();
+
+    for (i, (score, positive)) in scores.iter().enumerate() {
+        if i == 0 || *score != scores[i - 1].0 {
+            counts.push((0, 0));
+        }
 
-        if metric.eq_ignore_ascii_case("accuracy") {
-            let total_correct_predictions = confusion_matrix.diagonal().sum();
-            let total_predictions = confusion_matrix.sum();
-            score = total_correct_predictions as f32 / total_predictions as f32;
+        let last = counts.len() - 1;
 
-            print!(" {}: {:.0}%", metric, (score) * 100.0);
+        if *positive {
+            counts[last].0 += 1;
         } else {
-            if metric.eq_ignore_ascii_case("precision") {
-                score = class_confusion_matrices
-                    .iter()
-                    .map(|cm| cm.precision())
-                    .sum::<f32>();
-            }
-
-            if metric.eq_ignore_ascii_case("recall") {
-                score = class_confusion_matrices
-                    .iter()
-                    .map(|cm| cm.recall())
-                    .sum::<f32>();
-            }
-
-            if metric.eq_ignore_ascii_case("f1-score") {
-                score = class_confusion_matrices
-                    .iter()
-                    .map(|cm| cm.f1_score())
-                    .sum::<f32>();
-            }
-
-            print!(
-                " {}: {:.0}%",
-                metric,
-                (score / class_confusion_matrices.len() as f32) * 100.0
-            );
+            counts[last].1 += 1;
         }
-    })
+    }
+
+    counts
+}
+
+// Area under the ROC curve of (score, positive) pairs, as the probability that a
+// positive scores above a negative, with ties counting half
+pub fn roc_auc(scores: &[(f64, bool)]) -> f32 {
+    roc_auc_of_counts(&score_counts(scores))
+}
+
+// Same from the (positives, negatives) of ascending scores, or of score bins
+pub fn roc_auc_of_counts(counts: &[(usize, usize)]) -> f32 {
+    let positives: usize = counts.iter().map(|(positives, _)| positives).sum();
+    let negatives: usize = counts.iter().map(|(_, negatives)| negatives).sum();
+
+    if positives == 0 || negatives == 0 {
+        return 0.0;
+    }
+
+    let mut wins = 0.0;
+    let mut negatives_below = 0;
+
+    for &(group_positives, group_negatives) in counts {
+        wins += group_positives as f64 * (negatives_below as f64 + group_negatives as f64 / 2.0);
+
+        negatives_below += group_negatives;
+    }
+
+    (wins / (positives as f64 * negatives as f64)) as f32
+}
+
+// Area under the precision-recall curve as the average precision: the precision at
+// every score threshold weighted by the recall it adds
+pub fn average_precision(scores: &[(f64, bool)]) -> f32 {
+    average_precision_of_counts(&score_counts(scores))
+}
+
+pub fn average_precision_of_counts(counts: &[(usize, usize)]) -> f32 {
+    let positives: usize = counts.iter().map(|(positives, _)| positives).sum();
+
+    if positives == 0 {
+        return 0.0;
+    }
+
+    let mut average_precision = 0.0;
+    let mut true_positives = 0;
+    let mut predicted = 0;
+
+    for &(group_positives, group_negatives) in counts.iter().rev() {
+        true_positives += group_positives;
+        predicted += group_positives + group_negatives;
+
+        if group_positives > 0 {
+            average_precision += group_positives as f64 / positives as f64 * true_positives as f64
+                / predicted as f64;
+        }
+    }
+
+    average_precision as f32
 }
 
 pub fn get_class_confusion_matrices(
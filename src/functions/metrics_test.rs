@@ -2,10 +2,27 @@
 mod tests {
     use nalgebra::DMatrix;
 
-    use crate::functions::metrics::{
-        calculate_confusion_matrix, get_class_confusion_matrices,
+    use crate::{
+        error::NeuraError,
+        functions::{
+            metric::{Metric, MetricKind},
+            metrics::{
+                add_to_confusion_matrix, average_precision, balanced_accuracy,
+                calculate_confusion_matrix, cohen_kappa, get_class_confusion_matrices,
+                hamming_loss, label_f1_scores, log_loss, matthews_correlation, mean_absolute_error,
+                mean_absolute_percentage_error, r2_score, roc_auc, root_mean_squared_error,
+                subset_accuracy, top_k_accuracy,
+            },
+        },
     };
 
+    fn matrices(values: &[&[f32]]) -> Vec<DMatrix<f32>> {
+        values
+            .iter()
+            .map(|values| DMatrix::from_column_slice(values.len(), 1, values))
+            .collect()
+    }
+
     #[test]
     fn test_calculate_confusion_matrix() {
         let y_pred = vec![
@@ -22,7 +39,7 @@ mod tests {
             DMatrix::from_vec(3, 1, vec![0.0, 1.0, 0.0]),
         ];
 
-        let confusion_matrix = calculate_confusion_matrix(&y_pred, &y_true);
+        let confusion_matrix = calculate_confusion_matrix(&y_pred, &y_true).unwrap();
 
         assert_eq!(
             DMatrix::from_vec(3, 3, vec![1, 0, 0, 0, 1, 0, 0, 1, 1]),
@@ -30,6 +47,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_classes_out_of_the_confusion_matrix_are_errors() {
+        // A binary target of class 3
+        assert!(matches!(
+            calculate_confusion_matrix(&matrices(&[&[0.7]]), &matrices(&[&[3.0]])),
+            Err(NeuraError::ShapeMismatch {
+                expected: (4, 4),
+                found: (2, 2),
+                ..
+            })
+        ));
+
+        // Sized from the first prediction, the next one has more classes
+        let mut confusion_matrix = DMatrix::zeros(0, 0);
+
+        add_to_confusion_matrix(
+            &mut confusion_matrix,
+            &DMatrix::from_vec(2, 1, vec![0.9_f32, 0.1]),
+            &DMatrix::from_vec(2, 1, vec![1.0, 0.0]),
+        )
+        .unwrap();
+
+        assert!(add_to_confusion_matrix(
+            &mut confusion_matrix,
+            &DMatrix::from_vec(3, 1, vec![0.1_f32, 0.1, 0.8]),
+            &DMatrix::from_vec(3, 1, vec![0.0, 0.0, 1.0]),
+        )
+        .is_err());
+        assert_eq!(DMatrix::from_vec(2, 2, vec![1, 0, 0, 0]), confusion_matrix);
+    }
+
     #[test]
     fn test_metrics() {
         let confusion_matrix = DMatrix::from_vec(3, 3, vec![1, 0, 0, 0, 1, 0, 0, 1, 1]);
@@ -45,8 +93,8 @@ mod tests {
         assert_eq!(1.0, class_confusion_matrices[2].recall());
 
         assert_eq!(1.0, class_confusion_matrices[0].f1_score());
-        assert_eq!(0.6666666666666666, class_confusion_matrices[1].f1_score());
-        assert_eq!(0.6666666666666666, class_confusion_matrices[2].f1_score());
+        assert_eq!(2.0 / 3.0, class_confusion_matrices[1].f1_score());
+        assert_eq!(2.0 / 3.0, class_confusion_matrices[2].f1_score());
 
         let total_correct_predictions = confusion_matrix.diagonal().sum();
         let total_predictions = confusion_matrix.sum();
@@ -55,7 +103,7 @@ mod tests {
         assert_eq!(0.75, overall_accuracy);
 
         assert_eq!(
-            0.8333333333333334,
+            5.0 / 6.0,
             class_confusion_matrices
                 .iter()
                 .map(|cm| cm.precision())
@@ -64,7 +112,7 @@ mod tests {
         );
 
         assert_eq!(
-            0.8333333333333334,
+            5.0 / 6.0,
             class_confusion_matrices
                 .iter()
                 .map(|cm| cm.recall())
@@ -81,4 +129,120 @@ mod tests {
                 / class_confusion_matrices.len() as f32
         );
     }
+
+    #[test]
+    fn test_regression_metrics() {
+        let predictions = matrices(&[&[2.0], &[4.0], &[6.0], &[10.0]]);
+        let targets = matrices(&[&[1.0], &[4.0], &[8.0], &[10.0]]);
+
+        assert_eq!(0.75, mean_absolute_error(&predictions, &targets));
+        assert_eq!(
+            5.0_f32.sqrt() / 2.0,
+            root_mean_squared_error(&predictions, &targets)
+        );
+        assert_eq!(
+            0.3125,
+            mean_absolute_percentage_error(&predictions, &targets)
+        );
+        // Targets with mean 5.75 have a total sum of squares of 48.75
+        assert_eq!(1.0 - 5.0 / 48.75, r2_score(&predictions, &targets));
+    }
+
+    #[test]
+    fn test_multi_label_metrics() {
+        let predictions = matrices(&[&[0.9, 0.2, 0.6], &[0.4, 0.7, 0.1], &[0.8, 0.3, 0.4]]);
+        let targets = matrices(&[&[1.0, 0.0, 1.0], &[0.0, 1.0, 1.0], &[1.0, 1.0, 0.0]]);
+
+        assert_eq!(2.0 / 9.0, hamming_loss(&predictions, &targets, 0.5));
+        assert_eq!(1.0 / 3.0, subset_accuracy(&predictions, &targets, 0.5));
+        assert_eq!(0.0, subset_accuracy(&predictions, &targets, 0.05));
+
+        assert_eq!(
+            vec![1.0, 2.0 / 3.0, 2.0 / 3.0],
+            label_f1_scores(&predictions, &targets, &[0.5, 0.5, 0.5]).unwrap()
+        );
+        assert_eq!(
+            vec![1.0, 1.0, 0.8],
+            label_f1_scores(&predictions, &targets, &[0.5, 0.25, 0.05]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_metrics_do_not_need_one_hot_targets() {
+        let predictions = matrices(&[&[0.2], &[0.7], &[0.9]]);
+        let targets = matrices(&[&[0.0], &[0.0], &[1.0]]);
+
+        let mut metrics: Vec<Box<dyn Metric>> = vec![
+            MetricKind::Accuracy.into(),
+            MetricKind::MeanAbsoluteError.into(),
+            MetricKind::LabelF1(0, 0.8).into(),
+        ];
+
+        for metric in metrics.iter_mut() {
+            predictions
+                .iter()
+                .zip(targets.iter())
+                .for_each(|(prediction, target)| metric.update(prediction, target).unwrap());
+        }
+
+        assert_eq!(
+            vec![
+                ("accuracy".to_string(), 2.0 / 3.0),
+                ("mae".to_string(), (0.2 + 0.7 + 0.1) / 3.0),
+                ("label-f1[0]@0.8".to_string(), 1.0),
+            ],
+            metrics
+                .iter()
+                .map(|metric| (metric.name(), metric.result()))
+                .collect::<Vec<(String, f32)>>()
+        );
+
+        // Regression targets beyond the classes of the outputs
+        let targets = matrices(&[&[3.5, 1.0], &[-2.0, 0.0]]);
+
+        assert_eq!(1.0, MetricKind::Accuracy.score(&targets, &targets).unwrap());
+    }
+
+    #[test]
+    fn test_ranking_metrics() {
+        let predictions = matrices(&[&[0.5, 0.3, 0.2], &[0.1, 0.3, 0.6], &[0.2, 0.5, 0.3]]);
+        let targets = matrices(&[&[0.0, 1.0, 0.0], &[0.0, 0.0, 1.0], &[1.0, 0.0, 0.0]]);
+
+        assert_eq!(1.0 / 3.0, top_k_accuracy(&predictions, &targets, 1));
+        assert_eq!(2.0 / 3.0, top_k_accuracy(&predictions, &targets, 2));
+        assert_eq!(1.0, top_k_accuracy(&predictions, &targets, 3));
+
+        let scores = [(0.1, false), (0.4, false), (0.35, true), (0.8, true)];
+
+        assert_eq!(0.75, roc_auc(&scores));
+        assert!((average_precision(&scores) - 5.0 / 6.0).abs() < 1e-6);
+        // A tie between a positive and a negative counts half
+        assert_eq!(0.5, roc_auc(&[(0.5, true), (0.5, false)]));
+
+        let loss = log_loss(&matrices(&[&[0.8], &[0.4]]), &matrices(&[&[1.0], &[0.0]]));
+
+        assert!((loss as f64 - (0.8_f64.ln() + 0.6_f64.ln()) / -2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_agreement_metrics() {
+        // 2 classes: 4 right of class 0, 1 of class 0 predicted as 1, 1 right of class 1
+        let confusion_matrix = DMatrix::from_row_slice(2, 2, &[4, 1, 0, 1]);
+
+        assert_eq!((0.8 + 1.0) / 2.0, balanced_accuracy(&confusion_matrix));
+        // Expected agreement (5 * 4 + 1 * 2) / 36
+        assert_eq!(
+            ((5.0 / 6.0 - 22.0 / 36.0) / (1.0 - 22.0 / 36.0)) as f32,
+            cohen_kappa(&confusion_matrix)
+        );
+        assert_eq!(
+            (4.0 / (5.0_f64 * 2.0 * 4.0).sqrt()) as f32,
+            matthews_correlation(&confusion_matrix)
+        );
+
+        let perfect = DMatrix::from_row_slice(3, 3, &[2, 0, 0, 0, 3, 0, 0, 0, 1]);
+
+        assert_eq!(1.0, matthews_correlation(&perfect));
+        assert_eq!(1.0, cohen_kappa(&perfect));
+    }
 }
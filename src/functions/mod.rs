@@ -1,5 +1,10 @@
 pub mod activations;
 pub mod losses;
 mod losses_test;
+pub mod metric;
+mod metric_test;
 pub mod metrics;
 mod metrics_test;
+pub mod registry;
+pub mod report;
+mod report_test;
@@ -0,0 +1,210 @@
+use crate::core::{activation::Activation, float::Float, loss::Loss};
+
+use super::{
+    activations::{
+        relu, relu_derivative, relu_on_tape, sigmoid, sigmoid_derivative, sigmoid_on_tape, softmax,
+        softmax_derivative, softmax_on_tape, tanh_on_tape,
+    },
+    losses::{
+        binary_crossentropy, binary_crossentropy_derivative, binary_crossentropy_on_tape,
+        categorical_crossentropy, categorical_crossentropy_derivative,
+        categorical_crossentropy_on_tape, mse, mse_derivative, mse_on_tape, squared_error,
+        squared_error_derivative, squared_error_on_tape,
+    },
+    metric::MetricKind,
+};
+
+// Names of the built-in functions, used to save models and to read them from configs.
+// The functions keep their name, so the models using them can be saved.
+
+pub fn activations<T: Float>() -> Vec<(&'static str, Activation<T>)> {
+    [
+        (
+            "sigmoid",
+            Activation::Explicit {
+                function: sigmoid,
+                derivative: sigmoid_derivative,
+                name: None,
+            },
+        ),
+        (
+            "relu",
+            Activation::Explicit {
+                function: relu,
+                derivative: relu_derivative,
+                name: None,
+            },
+        ),
+        (
+            "softmax",
+            Activation::Explicit {
+                function: softmax,
+                derivative: softmax_derivative,
+                name: None,
+            },
+        ),
+        (
+            "sigmoid_on_tape",
+            Activation::Tape {
+                function: sigmoid_on_tape,
+                name: None,
+            },
+        ),
+        (
+            "relu_on_tape",
+            Activation::Tape {
+                function: relu_on_tape,
+                name: None,
+            },
+        ),
+        (
+            "tanh_on_tape",
+            Activation::Tape {
+                function: tanh_on_tape,
+                name: None,
+            },
+        ),
+        (
+            "softmax_on_tape",
+            Activation::Tape {
+                function: softmax_on_tape,
+                name: None,
+            },
+        ),
+    ]
+    .into_iter()
+    .map(|(name, activation)| (name, activation.named(name)))
+    .collect()
+}
+
+pub fn losses<T: Float>() -> Vec<(&'static str, Loss<T>)> {
+    [
+        (
+            "mse",
+            Loss::Explicit {
+                function: mse,
+                derivative: mse_derivative,
+                name: None,
+            },
+        ),
+        (
+            "squared_error",
+            Loss::Explicit {
+                function: squared_error,
+                derivative: squared_error_derivative,
+                name: None,
+            },
+        ),
+        (
+            "categorical_crossentropy",
+            Loss::Explicit {
+                function: categorical_crossentropy,
+                derivative: categorical_crossentropy_derivative,
+                name: None,
+            },
+        ),
+        (
+            "binary_crossentropy",
+            Loss::Explicit {
+                function: binary_crossentropy,
+                derivative: binary_crossentropy_derivative,
+                name: None,
+            },
+        ),
+        (
+            "mse_on_tape",
+            Loss::Tape {
+                function: mse_on_tape,
+                name: None,
+            },
+        ),
+        (
+            "squared_error_on_tape",
+            Loss::Tape {
+                function: squared_error_on_tape,
+                name: None,
+            },
+        ),
+        (
+            "categorical_crossentropy_on_tape",
+            Loss::Tape {
+                function: categorical_crossentropy_on_tape,
+                name: None,
+            },
+        ),
+        (
+            "binary_crossentropy_on_tape",
+            Loss::Tape {
+                function: binary_crossentropy_on_tape,
+                name: None,
+            },
+        ),
+    ]
+    .into_iter()
+    .map(|(name, loss)| (name, loss.named(name)))
+    .collect()
+}
+
+pub fn activation_by_name<T: Float>(name: &str) -> Option<Activation<T>> {
+    activations::<T>()
+        .into_iter()
+        .find(|(activation_name, _)| *activation_name == name)
+        .map(|(_, activation)| activation)
+}
+
+pub fn loss_by_name<T: Float>(name: &str) -> Option<Loss<T>> {
+    losses::<T>()
+        .into_iter()
+        .find(|(loss_name, _)| *loss_name == name)
+        .map(|(_, loss)| loss)
+}
+
+// Inverse of MetricKind::name
+pub fn metric_by_name(name: &str) -> Option<MetricKind> {
+    let metrics = [
+        MetricKind::Accuracy,
+        MetricKind::Precision,
+        MetricKind::Recall,
+        MetricKind::F1Score,
+        MetricKind::BalancedAccuracy,
+        MetricKind::MatthewsCorrelation,
+        MetricKind::CohenKappa,
+        MetricKind::RocAuc,
+        MetricKind::PrAuc,
+        MetricKind::LogLoss,
+        MetricKind::MeanAbsoluteError,
+        MetricKind::RootMeanSquaredError,
+        MetricKind::R2Score,
+        MetricKind::MeanAbsolutePercentageError,
+    ];
+
+    if let Some(metric) = metrics.into_iter().find(|metric| metric.name() == name) {
+        return Some(metric);
+    }
+
+    // The parameters are the k of top-k-accuracy, and the label and threshold
+    // after the @ of the multi-label metrics
+    match name.split_once('@') {
+        Some((metric, threshold)) => {
+            let threshold = threshold.parse().ok()?;
+
+            match metric {
+                "hamming-loss" => Some(MetricKind::HammingLoss(threshold)),
+                "subset-accuracy" => Some(MetricKind::SubsetAccuracy(threshold)),
+                _ => metric
+                    .strip_prefix("label-f1[")?
+                    .strip_suffix(']')?
+                    .parse()
+                    .ok()
+                    .map(|label| MetricKind::LabelF1(label, threshold)),
+            }
+        }
+        None => name
+            .strip_prefix("top-")?
+            .strip_suffix("-accuracy")?
+            .parse()
+            .ok()
+            .filter(|&k| k > 0)
+            .map(MetricKind::TopKAccuracy),
+    }
+}
@@ -0,0 +1,193 @@
+use std::{fmt, fs::File, io, path::Path};
+
+use nalgebra::DMatrix;
+
+use super::metrics::{get_class_confusion_matrices, ClassConfusionMatrix};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassScores {
+    pub precision: f32,
+    pub recall: f32,
+    pub f1_score: f32,
+    pub support: usize,
+}
+
+impl ClassScores {
+    fn new(confusion_matrix: &ClassConfusionMatrix) -> Self {
+        Self {
+            precision: confusion_matrix.precision(),
+            recall: confusion_matrix.recall(),
+            f1_score: confusion_matrix.f1_score(),
+            support: confusion_matrix.true_positives + confusion_matrix.false_negatives,
+        }
+    }
+}
+
+// Scores of every class and their averages: macro weighs the classes equally,
+// weighted by their support, and micro sums the counts of all the classes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassificationReport {
+    pub class_names: Vec<String>,
+    pub classes: Vec<ClassScores>,
+    pub accuracy: f32,
+    pub macro_average: ClassScores,
+    pub weighted_average: ClassScores,
+    pub micro_average: ClassScores,
+}
+
+// Classes without a name are named by their index
+fn class_names(classes: usize, names: Option<&[String]>) -> Vec<String> {
+    (0..classes)
+        .map(|class| {
+            names
+                .and_then(|names| names.get(class))
+                .cloned()
+                .unwrap_or_else(|| class.to_string())
+        })
+        .collect()
+}
+
+// The rows of the confusion matrix are the actual classes and the columns the predicted ones
+pub fn classification_report(
+    confusion_matrix: &DMatrix<usize>,
+    names: Option<&[String]>,
+) -> ClassificationReport {
+    let class_confusion_matrices = get_class_confusion_matrices(confusion_matrix);
+
+    let classes: Vec<ClassScores> = class_confusion_matrices
+        .iter()
+        .map(ClassScores::new)
+        .collect();
+
+    let samples = confusion_matrix.sum();
+    let class_count = classes.len().max(1) as f32;
+
+    let average = |weight: &dyn Fn(&ClassScores) -> f32, total: f32| {
+        let mean = |score: fn(&ClassScores) -> f32| {
+            if total == 0.0 {
+                0.0
+            } else {
+                classes
+                    .iter()
+                    .map(|class| score(class) * weight(class))
+                    .sum::<f32>()
+                    / total
+            }
+        };
+
+        ClassScores {
+            precision: mean(|class| class.precision),
+            recall: mean(|class| class.recall),
+            f1_score: mean(|class| class.f1_score),
+            support: samples,
+        }
+    };
+
+    let micro_average = ClassScores::new(&class_confusion_matrices.iter().fold(
+        ClassConfusionMatrix::empty(),
+        |mut sum, class| {
+            sum.true_positives += class.true_positives;
+            sum.false_positives += class.false_positives;
+            sum.false_negatives += class.false_negatives;
+            sum.true_negatives += class.true_negatives;
+
+            sum
+        },
+    ));
+
+    ClassificationReport {
+        class_names: class_names(classes.len(), names),
+        accuracy: if samples == 0 {
+            0.0
+        } else {
+            confusion_matrix.trace() as f32 / samples as f32
+        },
+        macro_average: average(&|_| 1.0, class_count),
+        weighted_average: average(&|class| class.support as f32, samples as f32),
+        micro_average: ClassScores {
+            support: samples,
+            ..micro_average
+        },
+        classes,
+    }
+}
+
+impl fmt::Display for ClassificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .class_names
+            .iter()
+            .map(String::len)
+            .chain(["weighted avg".len()])
+            .max()
+            .unwrap_or(0);
+
+        writeln!(
+            f,
+            "{:>width$} {:>9} {:>9} {:>9} {:>9}",
+            "", "precision", "recall", "f1-score", "support"
+        )?;
+        writeln!(f)?;
+
+        let row = |f: &mut fmt::Formatter<'_>, name: &str, scores: &ClassScores| {
+            writeln!(
+                f,
+                "{:>width$} {:>9.4} {:>9.4} {:>9.4} {:>9}",
+                name, scores.precision, scores.recall, scores.f1_score, scores.support
+            )
+        };
+
+        for (name, scores) in self.class_names.iter().zip(self.classes.iter()) {
+            row(f, name, scores)?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>width$} {:>9} {:>9} {:>9.4} {:>9}",
+            "accuracy", "", "", self.accuracy, self.macro_average.support
+        )?;
+        row(f, "macro avg", &self.macro_average)?;
+        row(f, "weighted avg", &self.weighted_average)?;
+        row(f, "micro avg", &self.micro_average)
+    }
+}
+
+// Writes the confusion matrix as CSV, with a header of the predicted classes and
+// the actual class at the start of every row
+pub fn write_confusion_matrix_csv<W: io::Write>(
+    writer: W,
+    confusion_matrix: &DMatrix<usize>,
+    names: Option<&[String]>,
+) -> Result<(), csv::Error> {
+    let names = class_names(confusion_matrix.nrows(), names);
+
+    let mut writer = csv::Writer::from_writer(writer);
+
+    writer.write_record(
+        std::iter::once("actual\\predicted").chain(names.iter().map(String::as_str)),
+    )?;
+
+    for (class, name) in names.iter().enumerate() {
+        writer.write_record(
+            std::iter::once(name.clone()).chain(
+                confusion_matrix
+                    .row(class)
+                    .iter()
+                    .map(|count| count.to_string()),
+            ),
+        )?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+pub fn save_confusion_matrix_csv<P: AsRef<Path>>(
+    path: P,
+    confusion_matrix: &DMatrix<usize>,
+    names: Option<&[String]>,
+) -> Result<(), csv::Error> {
+    write_confusion_matrix_csv(File::create(path)?, confusion_matrix, names)
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::functions::report::{classification_report, write_confusion_matrix_csv};
+
+    // Actual classes in the rows: 3 cats, 1 of them predicted as dog, and 1 dog
+    fn confusion_matrix() -> DMatrix<usize> {
+        DMatrix::from_row_slice(2, 2, &[2, 1, 0, 1])
+    }
+
+    #[test]
+    fn test_classification_report() {
+        let names = vec!["cat".to_string()];
+
+        let report = classification_report(&confusion_matrix(), Some(&names));
+
+        assert_eq!(vec!["cat", "1"], report.class_names);
+        assert_eq!(
+            vec![3, 1],
+            report
+                .classes
+                .iter()
+                .map(|class| class.support)
+                .collect::<Vec<usize>>()
+        );
+        assert_eq!(1.0, report.classes[0].precision);
+        assert_eq!(2.0 / 3.0, report.classes[0].recall);
+        assert_eq!(0.5, report.classes[1].precision);
+
+        assert_eq!(0.75, report.macro_average.precision);
+        assert_eq!((3.0 * 1.0 + 0.5) / 4.0, report.weighted_average.precision);
+        assert_eq!(0.75, report.micro_average.precision);
+        assert_eq!(0.75, report.micro_average.recall);
+        assert_eq!(0.75, report.accuracy);
+        assert_eq!(4, report.weighted_average.support);
+
+        let printed = report.to_string();
+
+        assert!(printed.contains("weighted avg"));
+        assert!(printed
+            .lines()
+            .any(|line| line.trim_start().starts_with("cat")));
+    }
+
+    #[test]
+    fn test_confusion_matrix_csv() {
+        let mut csv = Vec::new();
+
+        write_confusion_matrix_csv(
+            &mut csv,
+            &confusion_matrix(),
+            Some(&["cat".to_string(), "dog".to_string()]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            "actual\\predicted,cat,dog\ncat,2,1\ndog,0,1\n",
+            String::from_utf8(csv).unwrap()
+        );
+    }
+}
@@ -0,0 +1,10 @@
+pub mod autograd;
+pub mod config;
+mod config_test;
+pub mod core;
+pub mod data;
+pub mod error;
+pub mod functions;
+pub mod optimizers;
+pub mod prelude;
+pub mod preprocessing;
@@ -1,79 +1,135 @@
-#![allow(warnings)]
 use std::env;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use std::time::Instant;
 
 use nalgebra::DMatrix;
 
-use crate::core::model::Model;
-use crate::model_handler::get_trained_model;
+use neura_rust::{core::model::Model, preprocessing::encoders::LabelEncoder};
 
-use actix_cors::Cors;
-use actix_web::{web, App, HttpResponse, HttpServer, Responder};
-use serde::{Deserialize, Serialize};
+use crate::api::{
+    input_column, json_error, ApiError, ApiResponse, BatchData, BatchResponse, MatrixData,
+    ModelInfo, Prediction,
+};
+use crate::batcher::Batcher;
+use crate::cli::{parse_args, Command, ServeOptions, USAGE};
+use crate::model_handler::{evaluate, predict, train};
 
-mod core;
-mod functions;
+use actix_cors::Cors;
+use actix_web::{http::StatusCode, web, App, HttpResponse, HttpServer};
+
+mod api;
+mod api_test;
+mod batcher;
+mod batcher_test;
+mod cli;
+mod cli_test;
 mod model_handler;
-mod optimizers;
 
-#[derive(Deserialize)]
-struct MatrixData {
-    matrix: Vec<Vec<f32>>,
+// The model is shared between the workers without a lock, every call keeps
+// the layer outputs to itself
+struct Server {
+    model: Arc<Model>,
+    info: ModelInfo,
+    batcher: Batcher,
+}
+
+impl Server {
+    fn labels(&self) -> Option<&LabelEncoder> {
+        self.model
+            .get_pipeline_reference()
+            .and_then(|pipeline| pipeline.get_labels_reference())
+    }
 }
 
-#[derive(Serialize)]
-struct ApiResponse {
-    prediction: f32,
-    accuracy: f32,
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
 }
 
 async fn upload_matrix(
     data: web::Json<MatrixData>,
-    shared: web::Data<Arc<Mutex<Model>>>,
-) -> impl Responder {
-    let rows = data.matrix.len();
-    let cols = if rows > 0 { data.matrix[0].len() } else { 0 };
-    let mut matrix_elements = Vec::with_capacity(rows * cols);
-
-    for row in &data.matrix {
-        for &val in row {
-            matrix_elements.push(val as f32);
-        }
-    }
-
-    let input_data = DMatrix::from_row_slice(matrix_elements.len(), 1, &matrix_elements);
-    let mut locked_model = shared.lock().unwrap();
+    server: web::Data<Server>,
+) -> Result<HttpResponse, ApiError> {
+    let start = Instant::now();
+
+    data.options.validate()?;
+
+    // Evaluated in a micro-batch with the concurrent uploads
+    let probabilities = server
+        .batcher
+        .submit(input_column(&data.matrix))
+        .await
+        .map_err(|_| {
+            ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "unavailable",
+                "The batcher stopped",
+            )
+        })??;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        model: server.info.clone(),
+        prediction: Prediction::new(&probabilities, server.labels(), data.options),
+        latency_ms: elapsed_ms(start),
+    }))
+}
 
-    let prediction = locked_model.evaluate(&input_data);
+async fn predict_batch(
+    data: web::Json<BatchData>,
+    server: web::Data<Server>,
+) -> Result<HttpResponse, ApiError> {
+    let start = Instant::now();
 
-    drop(locked_model);
+    data.options.validate()?;
 
-    let prediction_vec = prediction.data.as_vec();
-    let mut index_of_max: Option<usize> = prediction_vec
+    let inputs: Vec<DMatrix<f32>> = data
+        .inputs
         .iter()
-        .enumerate()
-        .max_by(|(_, a), (_, b)| a.total_cmp(b))
-        .map(|(index, _)| index);
-
-    if index_of_max.is_none() {
-        index_of_max = Some(99);
-    }
-
-    HttpResponse::Ok().json(ApiResponse {
-        prediction: index_of_max.unwrap() as f32,
-        accuracy: prediction_vec[index_of_max.unwrap()],
-    })
+        .map(|matrix| input_column(matrix))
+        .collect();
+    let model = Arc::clone(&server.model);
+
+    // Already a batch, evaluated off the async workers
+    let predictions = web::block(move || model.evaluate_batch(&inputs))
+        .await
+        .map_err(|error| {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                error.to_string(),
+            )
+        })??;
+
+    Ok(HttpResponse::Ok().json(BatchResponse {
+        model: server.info.clone(),
+        predictions: predictions
+            .iter()
+            .map(|probabilities| Prediction::new(probabilities, server.labels(), data.options))
+            .collect(),
+        latency_ms: elapsed_ms(start),
+    }))
 }
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    env::set_var("RUST_BACKTRACE", "1");
+async fn serve(options: ServeOptions) -> std::io::Result<()> {
+    let model = Model::<f32>::load(&options.model).map_err(|error| {
+        std::io::Error::other(format!(
+            "Could not load the model {}: {} (run the train command to create it)",
+            options.model, error
+        ))
+    })?;
 
-    println!("Training the network...");
+    let info = ModelInfo::from_file(&options.model, &std::fs::read(&options.model)?);
 
-    let model = get_trained_model();
+    println!(
+        "Loaded the model {} ({} {})",
+        options.model, info.name, info.version
+    );
 
-    let shared_data = Arc::new(Mutex::new(model.unwrap()));
+    let model = Arc::new(model);
+    let server = web::Data::new(Server {
+        batcher: Batcher::new(Arc::clone(&model), options.batch),
+        info,
+        model,
+    });
 
     println!("\n===== Started API =====");
 
@@ -86,10 +142,46 @@ async fn main() -> std::io::Result<()> {
 
         App::new()
             .wrap(cors)
-            .app_data(web::Data::new(shared_data.clone()))
+            .app_data(server.clone())
+            .app_data(web::JsonConfig::default().error_handler(|error, _| json_error(error).into()))
             .route("/upload", web::post().to(upload_matrix))
+            .route("/predict", web::post().to(predict_batch))
     })
-    .bind("127.0.0.1:8000")?
+    .bind(options.address)?
     .run()
     .await
 }
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    env::set_var("RUST_BACKTRACE", "1");
+
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+
+            std::process::exit(2);
+        }
+    };
+
+    let result = match command {
+        Command::Serve(options) => return serve(options).await,
+        Command::Train(options) => {
+            println!("Training the network...");
+
+            train(&options).map(|_| ())
+        }
+        Command::Evaluate(options) => evaluate(&options),
+        Command::Predict(options) => predict(&options),
+        Command::Help => {
+            println!("{}", USAGE);
+
+            Ok(())
+        }
+    };
+
+    result.map_err(|error| std::io::Error::other(error.to_string()))
+}
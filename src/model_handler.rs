@@ -1,136 +1,354 @@
-use csv::{self};
-use std::error::Error;
-use std::str::FromStr;
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, Write},
+    path::Path,
+};
 
+use csv::ReaderBuilder;
 use nalgebra::DMatrix;
 
-use crate::{
-    core::{layer::Layer, model::Model},
+use neura_rust::{
+    config::{DataFormat, TrainingConfig, DEFAULT_METRICS},
+    core::model::{FitOptions, Model},
+    data::{
+        augmentation::{Augmentation, Augmenter},
+        csv_loader::{load_csv, CsvError, CsvFormat, Features, Normalization},
+        dataset::Dataset,
+        npy::read_npy,
+        sampling::balanced_class_weights,
+        streaming::StreamingCsvDataset,
+    },
     functions::{
-        activations::{relu, relu_derivative, softmax, softmax_derivative},
-        losses::{categorical_crossentropy, categorical_crossentropy_derivative},
-    }, optimizers::rmsprop::RMSProp,
+        metric::Metric,
+        registry::metric_by_name,
+        report::{classification_report, save_confusion_matrix_csv},
+    },
+    optimizers::rmsprop::RMSProp,
+    preprocessing::{
+        encoders::LabelEncoder,
+        pipeline::{Pipeline, Transformer},
+        scalers::FixedRangeScaler,
+    },
 };
 
+use crate::cli::{EvaluateOptions, PredictOptions};
+
+// MNIST CSV: label in the column 0 and one pixel per column
+pub fn mnist_format() -> CsvFormat {
+    CsvFormat {
+        label_column: 0,
+        features: Features::AllColumns,
+        has_headers: true,
+        delimiter: b',',
+        normalization: Normalization::None,
+        classes: Some(10),
+        labels: None,
+    }
+}
+
+// Google's Quick, Draw Doodles dataset (personal file): label in the column 1 and
+// the bracketed pixels list in the column 2
+pub fn doodles_format() -> CsvFormat {
+    CsvFormat {
+        label_column: 1,
+        features: Features::Array(2),
+        has_headers: true,
+        delimiter: b',',
+        normalization: Normalization::None,
+        classes: Some(10),
+        labels: None,
+    }
+}
+
+pub fn csv_format(format: DataFormat) -> CsvFormat {
+    match format {
+        DataFormat::Doodles => doodles_format(),
+        DataFormat::Mnist => mnist_format(),
+    }
+}
+
+// Class index labels keep the index order, fit would sort "10" before "2".
+// Named labels are sorted.
+fn fit_labels(format: &CsvFormat, file_path: &str) -> Result<LabelEncoder, CsvError> {
+    let labels = format.read_labels(file_path)?;
+
+    let indices: Option<Vec<usize>> = labels.iter().map(|label| label.parse().ok()).collect();
+
+    Ok(match indices {
+        Some(indices) => {
+            let classes = indices
+                .iter()
+                .map(|&class| class + 1)
+                .chain(format.classes)
+                .max()
+                .unwrap_or(0);
+
+            LabelEncoder::from_classes((0..classes).map(|class| class.to_string()).collect())
+        }
+        None => {
+            let mut encoder = LabelEncoder::new();
+
+            encoder.fit(labels.iter().map(String::as_str));
+
+            encoder
+        }
+    })
+}
 
-// Read MNIST dataset
-// fn read_mnist(file_path: &str) -> Result<(Vec<DMatrix<f32>>, Vec<DMatrix<f32>>), Box<dyn Error>> {
-//     let mut rdr = csv::Reader::from_path(file_path)?;
+// The format reading the labels as the model classes
+fn model_format(model: &Model, format: DataFormat) -> CsvFormat {
+    let mut format = csv_format(format);
 
-//     let mut x: Vec<DMatrix<f32>> = Vec::new();
-//     let mut y: Vec<DMatrix<f32>> = Vec::new();
+    if let Some(labels) = model
+        .get_pipeline_reference()
+        .and_then(|pipeline| pipeline.get_labels_reference())
+    {
+        format.classes = Some(labels.get_classes_reference().len());
+        format.labels = Some(labels.clone());
+    }
 
-//     for result in rdr.records() {
-//         let record = result?;
+    format
+}
 
-//         let class = record[0].parse::<usize>().unwrap();
+// Unknown names are skipped, the configs are validated before
+fn metrics<S: AsRef<str>>(names: &[S]) -> Vec<Box<dyn Metric>> {
+    names
+        .iter()
+        .filter_map(|name| metric_by_name(name.as_ref()))
+        .map(Into::into)
+        .collect()
+}
 
-//         let float_record: Vec<f32> = record
-//             .iter()
-//             .map(|x| x.parse::<f32>().unwrap() / 255.0)
-//             .collect();
+// Tests the model and prints its classification report
+fn report(
+    model: &Model,
+    metrics: Vec<Box<dyn Metric>>,
+    dataset: &dyn Dataset,
+    confusion_matrix_path: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    model.test(metrics, dataset)?;
+
+    let confusion_matrix = model.confusion_matrix(dataset)?;
+    let class_names = model
+        .get_pipeline_reference()
+        .and_then(|pipeline| pipeline.get_labels_reference())
+        .map(|labels| labels.get_classes_reference().as_slice());
+
+    println!(
+        "\n{}",
+        classification_report(&confusion_matrix, class_names)
+    );
+
+    if let Some(path) = confusion_matrix_path {
+        save_confusion_matrix_csv(path, &confusion_matrix, class_names)?;
+    }
 
-//         x.push(DMatrix::from_vec(
-//             float_record.len() - 1,
-//             1,
-//             float_record[1..].to_vec(),
-//         ));
+    Ok(())
+}
 
-//         let mut label = DMatrix::zeros(1, 10);
+pub fn train(config: &TrainingConfig) -> Result<Model, Box<dyn Error>> {
+    config.validate()?;
 
-//         label[class] = 1.0;
+    let mut format = csv_format(config.data.format);
 
-//         y.push(label.transpose());
-//     }
+    let labels = fit_labels(&format, &config.data.train)?;
 
-//     Ok((x, y))
-// }
+    format.classes = Some(labels.get_classes_reference().len());
+    format.labels = Some(labels.clone());
 
-// Read Google's Quick, Draw Doodles dataset (personal file)
-fn read_doodles(file_path: &str) -> Result<(Vec<DMatrix<f32>>, Vec<DMatrix<f32>>), Box<dyn Error>> {
-    let mut rdr = csv::Reader::from_path(file_path)?;
+    let train_dataset =
+        StreamingCsvDataset::<f32>::open(&config.data.train, format.clone(), Some(4))?;
 
-    let mut x: Vec<DMatrix<f32>> = Vec::new();
-    let mut y: Vec<DMatrix<f32>> = Vec::new();
+    println!("Loaded data train = {}", train_dataset.len());
 
-    for result in rdr.records() {
-        let record = result?;
+    // Scaling the pixels in the model pipeline instead of the loader makes the
+    // server apply it as well. The range is the one of the pixels, not the one
+    // seen in the training set, so the unseen extremes keep their meaning.
+    // The labels are saved with it, for the class names of the reports and the
+    // server responses.
+    let mut pipeline = Pipeline::with_labels(
+        vec![Transformer::FixedRangeScaler(FixedRangeScaler::new(
+            0.0, 255.0,
+        ))],
+        labels,
+    );
 
-        let class = record[1].parse::<usize>().unwrap();
+    pipeline.fit(&train_dataset)?;
 
-        let numbers: Vec<f32> = record[2]
-            .trim_matches(|p| p == '[' || p == ']')
-            .split(',')
-            .map(|s| f32::from_str(s.trim()).unwrap() / 255.0)
-            .collect();
+    let (x_sample, y_sample) = train_dataset.get(0)?;
 
-        x.push(DMatrix::from_vec(numbers.len(), 1, numbers));
+    let mut model = config.build_model(x_sample.len(), y_sample.len())?;
 
-        let mut label = DMatrix::zeros(1, 10);
+    model.set_pipeline(Some(pipeline));
 
-        label[class] = 1.0;
+    // The doodle classes are skewed, so the rare ones weigh more in the loss
+    if config.data.class_weights {
+        model.set_class_weights(Some(balanced_class_weights(&train_dataset)?));
+    }
 
-        y.push(label.transpose());
+    // Saved before the training, so a run that fails still has its config
+    let config_path = config.effective_config_path();
+
+    config.effective(y_sample.len()).save(&config_path)?;
+
+    println!("Saved the effective config to {}", config_path.display());
+
+    let mut optimizer = RMSProp::new(config.optimizer.decay_rate);
+
+    // The doodles and MNIST digits are 28x28 images
+    let mut augmenter = Augmenter::new(
+        28,
+        28,
+        vec![
+            Augmentation::Rotation(10.0),
+            Augmentation::Scaling(0.9, 1.1),
+            Augmentation::Shift(2.0),
+        ],
+        42,
+    )?;
+
+    model.fit(
+        FitOptions {
+            batch_size: config.fit.batch_size,
+            epochs: config.fit.epochs,
+            learning_rate: config.fit.learning_rate,
+            schedule: config.fit.schedule,
+            metrics: metrics(&config.metrics),
+            augmenter: config.data.augment.then_some(&mut augmenter),
+            ..FitOptions::default()
+        },
+        &mut optimizer,
+        &train_dataset,
+    )?;
+
+    model.save(&config.output)?;
+
+    println!("\nSaved the model to {}", config.output);
+
+    if let Some(test_dataset) = &config.data.test {
+        let test_dataset = load_csv(test_dataset, &format)?;
+
+        println!("\nTesting the network on {} samples:\n", test_dataset.len());
+
+        let confusion_matrix_path =
+            Path::new(&config.output).with_file_name("confusion_matrix.csv");
+
+        report(
+            &model,
+            metrics(&config.metrics),
+            &test_dataset,
+            confusion_matrix_path.to_str(),
+        )?;
     }
 
-    Ok((x, y))
+    Ok(model)
 }
 
-pub fn get_trained_model() -> Option<Model>{
-    match (
-        read_doodles("./doodles/train-quick-draw.csv"),
-        read_doodles("./doodles/test-quick-draw.csv"),
-    ) {
-        (Ok((x_train, y_train)), Ok((x_test, y_test))) => {
-            println!(
-                "Loaded data x_train = {} y_train = {} x_test = {} y_test = {})",
-                x_train.len(),
-                y_train.len(),
-                x_test.len(),
-                y_test.len()
-            );
-            // println!("x[0] = {}", x_train[0]);
-
-            let hidden_layer1 = Layer::new(relu, relu_derivative, x_train[0].len(), 1024);
-
-            let hidden_layer2 = Layer::new(relu, relu_derivative, 1024, 512);
-
-            let output_layer = Layer::new(softmax, softmax_derivative, 512, y_train[0].len());
-
-            let mut model = Model::new(
-                vec![hidden_layer1, hidden_layer2, output_layer],
-                categorical_crossentropy,
-                categorical_crossentropy_derivative,
-            );
-
-            let mut rmsprop = RMSProp::new(0.9);
-
-            let metrics = vec![
-                "accuracy".to_string(),
-                "recall".to_string(),
-                "f1-score".to_string(),
-                "precision".to_string(),
-            ];
-
-            model.fit(
-                64,
-                10,
-                0.001,
-                metrics.clone(),
-                &mut rmsprop,
-                x_train,
-                y_train,
-            );
-
-            println!("\nTesting the network:\n");
-
-            model.test(metrics, &x_test, &y_test);
-
-            Some(model)
-        }
-        _ => {
-            println!("Error reading CSV file:");
-            None
+pub fn evaluate(options: &EvaluateOptions) -> Result<(), Box<dyn Error>> {
+    let model = Model::<f32>::load(&options.model)?;
+    let dataset = load_csv(&options.dataset, &model_format(&model, options.format))?;
+
+    println!(
+        "Evaluating {} on {} samples:\n",
+        options.model,
+        dataset.len()
+    );
+
+    report(
+        &model,
+        metrics(&DEFAULT_METRICS),
+        &dataset,
+        options.confusion_matrix.as_deref(),
+    )
+}
+
+// The inputs are the rows of a .npy array, or the lines of a CSV without headers
+// holding the features in columns or as a bracketed list
+fn read_inputs(path: &str) -> Result<Vec<DMatrix<f32>>, Box<dyn Error>> {
+    let rows: Vec<Vec<f64>> = if path.ends_with(".npy") {
+        let array = read_npy(path)?;
+
+        (0..array.len()).map(|i| array.row(i).to_vec()).collect()
+    } else {
+        let mut reader = ReaderBuilder::new().has_headers(false).from_path(path)?;
+        let mut rows = Vec::new();
+
+        for (line, record) in reader.records().enumerate() {
+            let record = record?;
+
+            let values: Result<Vec<f64>, _> = record
+                .iter()
+                .flat_map(|field| field.trim_matches(|c| c == '[' || c == ']').split(','))
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+                .map(str::parse)
+                .collect();
+
+            match values {
+                Ok(values) => rows.push(values),
+                // A header line
+                Err(_) if line == 0 => continue,
+                Err(_) => {
+                    return Err(
+                        format!("Invalid number in the line {} of {}", line + 1, path).into(),
+                    )
+                }
+            }
         }
+
+        rows
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|row| DMatrix::from_iterator(row.len(), 1, row.into_iter().map(|value| value as f32)))
+        .collect())
+}
+
+// Writes index,class,label,score for every input
+pub fn predict(options: &PredictOptions) -> Result<(), Box<dyn Error>> {
+    let model = Model::<f32>::load(&options.model)?;
+    let inputs = read_inputs(&options.input)?;
+
+    let labels = model
+        .get_pipeline_reference()
+        .and_then(|pipeline| pipeline.get_labels_reference())
+        .cloned();
+
+    let output: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let mut writer = csv::Writer::from_writer(output);
+
+    writer.write_record(["index", "class", "label", "score"])?;
+
+    for (index, input) in inputs.iter().enumerate() {
+        let prediction = model
+            .evaluate(input)
+            .map_err(|error| format!("Input {}: {}", index, error))?;
+
+        let (class, score) = prediction
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or((0, 0.0), |(class, score)| (class, *score));
+
+        let label = labels
+            .as_ref()
+            .and_then(|labels| labels.inverse_transform(class))
+            .unwrap_or_default();
+
+        writer.write_record([
+            index.to_string(),
+            class.to_string(),
+            label.to_string(),
+            score.to_string(),
+        ])?;
     }
-}
\ No newline at end of file
+
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use crate::core::{float::Float, layer::Layer};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientClipping<T: Float = f32> {
+    // Clamp every averaged gradient component to [-value, value]
+    Value(T),
+    // Rescale all the averaged gradients so their global L2 norm is at most max_norm
+    GlobalNorm(T),
+}
+
+// L2 norm of the batch averaged gradients of all layers
+pub fn global_gradient_norm<T: Float>(layers: &[Layer<T>], batch_size: usize) -> T {
+    let squared_sum = layers.iter().fold(T::zero(), |sum, layer| {
+        sum + layer.get_squared_gradients_sum()
+    });
+
+    squared_sum.sqrt() / T::cast(batch_size as f64)
+}
+
+// The layers accumulate the gradients summed over the batch, so the limits are
+// scaled by the batch size to be applied to the averaged gradients.
+pub fn clip_gradients<T: Float>(
+    layers: &mut [Layer<T>],
+    batch_size: usize,
+    clipping: GradientClipping<T>,
+    global_norm: T,
+) {
+    match clipping {
+        GradientClipping::Value(value) => {
+            let limit = value * T::cast(batch_size as f64);
+
+            layers
+                .iter_mut()
+                .for_each(|layer| layer.clamp_errors_and_deltas(limit));
+        }
+        GradientClipping::GlobalNorm(max_norm) => {
+            if global_norm > max_norm {
+                let factor = max_norm / global_norm;
+
+                layers
+                    .iter_mut()
+                    .for_each(|layer| layer.scale_errors_and_deltas(factor));
+            }
+        }
+    }
+}
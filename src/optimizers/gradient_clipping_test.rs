@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        core::layer::Layer,
+        optimizers::gradient_clipping::{clip_gradients, global_gradient_norm, GradientClipping},
+    };
+
+    fn layer_with_gradients(errors: Vec<f32>, deltas: Vec<f32>) -> Layer {
+        let mut layer = Layer::from(
+            |x| x.clone(),
+            |x| x.clone(),
+            DMatrix::zeros(2, 1),
+            DMatrix::zeros(2, 1),
+        );
+
+        layer.sum_errors_and_deltas(
+            &DMatrix::from_vec(2, 1, deltas),
+            &DMatrix::from_vec(2, 1, errors),
+        );
+
+        layer
+    }
+
+    #[test]
+    fn test_global_gradient_norm() {
+        let layers = vec![
+            layer_with_gradients(vec![6.0, 0.0], vec![0.0, 0.0]),
+            layer_with_gradients(vec![0.0, 0.0], vec![0.0, 8.0]),
+        ];
+
+        assert_eq!(10.0, global_gradient_norm(&layers, 1));
+        assert_eq!(5.0, global_gradient_norm(&layers, 2));
+    }
+
+    #[test]
+    fn test_clip_by_value() {
+        let mut layers = vec![layer_with_gradients(vec![3.0, -5.0], vec![0.5, -0.5])];
+
+        clip_gradients(&mut layers, 2, GradientClipping::Value(1.0), 0.0);
+
+        assert_eq!(
+            DMatrix::from_vec(2, 1, vec![2.0, -2.0]),
+            layers[0].get_errors_clone()
+        );
+        assert_eq!(
+            DMatrix::from_vec(2, 1, vec![0.5, -0.5]),
+            layers[0].get_deltas_clone()
+        );
+    }
+
+    #[test]
+    fn test_clip_by_global_norm() {
+        let mut layers = vec![
+            layer_with_gradients(vec![6.0, 0.0], vec![0.0, 0.0]),
+            layer_with_gradients(vec![0.0, 0.0], vec![0.0, 8.0]),
+        ];
+
+        let norm = global_gradient_norm(&layers, 1);
+
+        clip_gradients(&mut layers, 1, GradientClipping::GlobalNorm(5.0), norm);
+
+        assert_eq!(5.0, global_gradient_norm(&layers, 1));
+        assert_eq!(
+            DMatrix::from_vec(2, 1, vec![3.0, 0.0]),
+            layers[0].get_errors_clone()
+        );
+
+        let norm = global_gradient_norm(&layers, 1);
+
+        clip_gradients(&mut layers, 1, GradientClipping::GlobalNorm(10.0), norm);
+
+        assert_eq!(5.0, global_gradient_norm(&layers, 1));
+    }
+}
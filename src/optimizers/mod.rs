@@ -1,2 +1,6 @@
+pub mod gradient_clipping;
+mod gradient_clipping_test;
 pub mod optimizer;
 pub mod rmsprop;
+pub mod schedule;
+mod schedule_test;
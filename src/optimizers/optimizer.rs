@@ -1,6 +1,6 @@
-use crate::core::layer::Layer;
+use crate::core::{float::Float, layer::Layer};
 
-pub trait Optimizer {
-    fn initialize_layer_additional_params(&self, layer: &mut Layer);
-    fn update_params(&mut self, batch_size: usize, layer: &mut Layer, learning_rate: f32);
+pub trait Optimizer<T: Float = f32> {
+    fn initialize_layer_additional_params(&self, layer: &mut Layer<T>);
+    fn update_params(&mut self, batch_size: usize, layer: &mut Layer<T>, learning_rate: T);
 }
@@ -2,14 +2,16 @@ use std::collections::HashMap;
 
 use nalgebra::DMatrix;
 
+use crate::core::{float::Float, layer::Layer};
+
 use super::optimizer::Optimizer;
 
-pub struct RMSProp {
-    decay_rate: f32,
+pub struct RMSProp<T: Float = f32> {
+    decay_rate: T,
 }
 
-impl Optimizer for RMSProp {
-    fn initialize_layer_additional_params(&self, layer: &mut crate::core::layer::Layer) {
+impl<T: Float> Optimizer<T> for RMSProp<T> {
+    fn initialize_layer_additional_params(&self, layer: &mut Layer<T>) {
         let input_dim = layer.get_input_dim();
         let output_dim = layer.get_output_dim();
 
@@ -25,12 +27,7 @@ impl Optimizer for RMSProp {
         );
     }
 
-    fn update_params(
-        &mut self,
-        batch_size: usize,
-        layer: &mut crate::core::layer::Layer,
-        learning_rate: f32,
-    ) {
+    fn update_params(&mut self, batch_size: usize, layer: &mut Layer<T>, learning_rate: T) {
         self.calculate_moving_avg(layer);
 
         let errors = layer.get_errors_clone();
@@ -44,47 +41,49 @@ impl Optimizer for RMSProp {
             )
         };
 
-        let mut w_step_sizes = weights_moving_avg.map(|x| learning_rate / (x + 1e-8).sqrt());
-        let mut b_step_sizes = biases_moving_avg.map(|x| learning_rate / (x + 1e-8).sqrt());
+        let mut w_step_sizes =
+            weights_moving_avg.map(|x| learning_rate / (x + T::cast(1e-8)).sqrt());
+        let mut b_step_sizes =
+            biases_moving_avg.map(|x| learning_rate / (x + T::cast(1e-8)).sqrt());
 
         w_step_sizes = w_step_sizes.component_mul(&errors);
 
         let weights_ref = layer.get_weights_mut_reference();
-        *weights_ref -= w_step_sizes.map(|x| x / batch_size as f32);
+        *weights_ref -= w_step_sizes.map(|x| x / T::cast(batch_size as f64));
 
         b_step_sizes = b_step_sizes.component_mul(&deltas);
 
         let biases_ref = layer.get_biases_mut_reference();
-        *biases_ref -= b_step_sizes.map(|x| x / batch_size as f32);
+        *biases_ref -= b_step_sizes.map(|x| x / T::cast(batch_size as f64));
     }
 }
 
-impl RMSProp {
-    pub fn new(decay_rate: f32) -> Self {
+impl<T: Float> RMSProp<T> {
+    pub fn new(decay_rate: T) -> Self {
         Self { decay_rate }
     }
-    fn calculate_moving_avg(&mut self, layer: &mut crate::core::layer::Layer) {
+    fn calculate_moving_avg(&mut self, layer: &mut Layer<T>) {
         let errors = layer.get_errors_clone();
         let deltas = layer.get_deltas_clone();
 
-        let mut optimizer_params = layer.get_optimizer_params_mut_reference();
+        let optimizer_params = layer.get_optimizer_params_mut_reference();
 
-        self.update_moving_avg(&mut optimizer_params, "weights_moving_avg", &errors);
+        self.update_moving_avg(optimizer_params, "weights_moving_avg", &errors);
 
-        self.update_moving_avg(&mut optimizer_params, "biases_moving_avg", &deltas);
+        self.update_moving_avg(optimizer_params, "biases_moving_avg", &deltas);
     }
 
     fn update_moving_avg(
         &self,
-        optimizer_params: &mut HashMap<String, DMatrix<f32>>,
+        optimizer_params: &mut HashMap<String, DMatrix<T>>,
         key: &str,
-        gradients: &DMatrix<f32>,
+        gradients: &DMatrix<T>,
     ) {
         if let Some(mut moving_avg) = optimizer_params.remove(key) {
             moving_avg.scale_mut(self.decay_rate);
 
             let mut squared_gradients = gradients.map(|x| x.powi(2));
-            squared_gradients.scale_mut(1.0 - self.decay_rate);
+            squared_gradients.scale_mut(T::one() - self.decay_rate);
 
             moving_avg += squared_gradients;
 
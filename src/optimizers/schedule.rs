@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{core::float::Float, error::NeuraError};
+
+// Learning rate of every epoch, from the learning rate of the first one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum LearningRateSchedule {
+    #[default]
+    Constant,
+    // Multiplied by factor after every `every` epochs
+    Step {
+        every: usize,
+        factor: f64,
+    },
+    // Multiplied by gamma after every epoch
+    Exponential {
+        gamma: f64,
+    },
+}
+
+impl LearningRateSchedule {
+    pub fn learning_rate<T: Float>(&self, initial: T, epoch: usize) -> T {
+        let factor = match *self {
+            LearningRateSchedule::Constant => 1.0,
+            LearningRateSchedule::Step { every, factor } => factor.powi((epoch / every) as i32),
+            LearningRateSchedule::Exponential { gamma } => gamma.powi(epoch as i32),
+        };
+
+        initial * T::cast(factor)
+    }
+
+    // The invalid field and why, the configs report it with their own path
+    pub fn invalid_field(&self) -> Option<(&'static str, &'static str)> {
+        let positive = |value: f64| value > 0.0 && value.is_finite();
+
+        match *self {
+            LearningRateSchedule::Constant => None,
+            LearningRateSchedule::Step { every: 0, .. } => Some(("every", "must be positive")),
+            LearningRateSchedule::Step { factor, .. } if !positive(factor) => {
+                Some(("factor", "must be a positive number"))
+            }
+            LearningRateSchedule::Exponential { gamma } if !positive(gamma) => {
+                Some(("gamma", "must be a positive number"))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn check(&self) -> Result<(), NeuraError> {
+        match self.invalid_field() {
+            Some((field, message)) => Err(NeuraError::InvalidArgument(format!(
+                "The {} of the learning rate schedule {}",
+                field, message
+            ))),
+            None => Ok(()),
+        }
+    }
+}
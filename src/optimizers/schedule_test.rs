@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use crate::optimizers::schedule::LearningRateSchedule;
+
+    #[test]
+    fn test_learning_rates_of_the_epochs() {
+        let rates = |schedule: LearningRateSchedule| {
+            (0..5)
+                .map(|epoch| schedule.learning_rate(0.1_f64, epoch))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![0.1; 5], rates(LearningRateSchedule::Constant));
+        assert_eq!(
+            vec![0.1, 0.1, 0.05, 0.05, 0.025],
+            rates(LearningRateSchedule::Step {
+                every: 2,
+                factor: 0.5
+            })
+        );
+        assert_eq!(
+            vec![0.1, 0.05, 0.025, 0.0125, 0.00625],
+            rates(LearningRateSchedule::Exponential { gamma: 0.5 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_schedules() {
+        assert!(LearningRateSchedule::Constant.check().is_ok());
+        assert_eq!(
+            Some(("every", "must be positive")),
+            LearningRateSchedule::Step {
+                every: 0,
+                factor: 0.5
+            }
+            .invalid_field()
+        );
+        assert!(LearningRateSchedule::Step {
+            every: 1,
+            factor: f64::NAN
+        }
+        .check()
+        .is_err());
+        assert!(LearningRateSchedule::Exponential { gamma: -0.5 }
+            .check()
+            .is_err());
+    }
+}
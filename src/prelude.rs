@@ -0,0 +1,35 @@
+// The types and functions most programs need, to be glob imported:
+// use neura_rust::prelude::*;
+
+pub use nalgebra::DMatrix;
+
+pub use crate::{
+    config::{ConfigError, TrainingConfig},
+    core::{
+        activation::Activation,
+        divergence::{DivergenceError, NonFiniteAction},
+        float::Float,
+        layer::Layer,
+        loss::Loss,
+        model::{FitOptions, Model},
+        workspace::Workspace,
+    },
+    data::dataset::{Dataset, InMemoryDataset},
+    error::NeuraError,
+    functions::{
+        activations::{
+            relu, relu_derivative, sigmoid, sigmoid_derivative, softmax, softmax_derivative,
+        },
+        losses::{
+            binary_crossentropy, binary_crossentropy_derivative, categorical_crossentropy,
+            categorical_crossentropy_derivative, mse, mse_derivative,
+        },
+        metric::{Metric, MetricKind},
+        registry::{activation_by_name, loss_by_name, metric_by_name},
+    },
+    optimizers::{
+        gradient_clipping::GradientClipping, optimizer::Optimizer, rmsprop::RMSProp,
+        schedule::LearningRateSchedule,
+    },
+    preprocessing::pipeline::Pipeline,
+};
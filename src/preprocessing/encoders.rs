@@ -0,0 +1,133 @@
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::{core::float::Float, error::NeuraError};
+
+// Replaces each of the categorical features by a one-hot block with a position
+// per category seen while fitting. Unseen categories get an all zeros block.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OneHotEncoder {
+    columns: Vec<usize>,
+    // Sorted categories of each column
+    categories: Vec<Vec<f64>>,
+}
+
+impl OneHotEncoder {
+    pub fn new(columns: Vec<usize>) -> Self {
+        Self {
+            categories: vec![Vec::new(); columns.len()],
+            columns,
+        }
+    }
+
+    // Fails on a row without all the encoded columns
+    pub fn check_width(&self, input: &[f64]) -> Result<(), NeuraError> {
+        match self.columns.iter().max() {
+            Some(&column) if column >= input.len() => Err(NeuraError::shape_mismatch(
+                "one-hot encoded input",
+                (column + 1, 1),
+                (input.len(), 1),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn observe(&mut self, input: &[f64]) -> Result<(), NeuraError> {
+        self.check_width(input)?;
+
+        for (categories, &column) in self.categories.iter_mut().zip(self.columns.iter()) {
+            let value = input[column];
+
+            if let Err(position) =
+                categories.binary_search_by(|category| category.total_cmp(&value))
+            {
+                categories.insert(position, value);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn transform(&self, input: &[f64]) -> Vec<f64> {
+        let mut output = Vec::with_capacity(input.len());
+
+        for (column, &value) in input.iter().enumerate() {
+            match self.columns.iter().position(|&encoded| encoded == column) {
+                Some(position) => {
+                    let categories = &self.categories[position];
+                    let hot = categories.binary_search_by(|category| category.total_cmp(&value));
+
+                    output.extend((0..categories.len()).map(
+                        |i| {
+                            if hot == Ok(i) {
+                                1.0
+                            } else {
+                                0.0
+                            }
+                        },
+                    ));
+                }
+                None => output.push(value),
+            }
+        }
+
+        output
+    }
+
+    pub fn get_categories_reference(&self) -> &Vec<Vec<f64>> {
+        &self.categories
+    }
+
+    pub(crate) fn reset(&self) -> Self {
+        Self::new(self.columns.clone())
+    }
+}
+
+// Maps the target labels, e.g. the Quick, Draw! words, to class indices and back
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LabelEncoder {
+    classes: Vec<String>,
+}
+
+impl LabelEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Keeps the order of the classes, unlike fit which sorts them
+    pub fn from_classes(classes: Vec<String>) -> Self {
+        Self { classes }
+    }
+
+    pub fn fit<'a>(&mut self, labels: impl IntoIterator<Item = &'a str>) {
+        for label in labels {
+            if self.transform(label).is_none() {
+                self.classes.push(label.to_string());
+            }
+        }
+
+        self.classes.sort();
+    }
+
+    pub fn transform(&self, label: &str) -> Option<usize> {
+        self.classes.iter().position(|class| class == label)
+    }
+
+    pub fn one_hot<T: Float>(&self, label: &str) -> Option<DMatrix<T>> {
+        self.transform(label).map(|class| {
+            let mut target = DMatrix::zeros(self.classes.len(), 1);
+
+            target[class] = T::one();
+
+            target
+        })
+    }
+
+    pub fn inverse_transform(&self, class: usize) -> Option<&str> {
+        self.classes.get(class).map(String::as_str)
+    }
+
+    pub fn get_classes_reference(&self) -> &Vec<String> {
+        &self.classes
+    }
+}
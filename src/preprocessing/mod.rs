@@ -0,0 +1,4 @@
+pub mod encoders;
+pub mod pipeline;
+mod pipeline_test;
+pub mod scalers;
@@ -0,0 +1,157 @@
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::{core::float::Float, data::dataset::Dataset, error::NeuraError};
+
+use super::{
+    encoders::{LabelEncoder, OneHotEncoder},
+    scalers::{FixedRangeScaler, MinMaxScaler, StandardScaler},
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Transformer {
+    StandardScaler(StandardScaler),
+    MinMaxScaler(MinMaxScaler),
+    FixedRangeScaler(FixedRangeScaler),
+    OneHotEncoder(OneHotEncoder),
+}
+
+impl Transformer {
+    pub fn observe(&mut self, input: &[f64]) -> Result<(), NeuraError> {
+        match self {
+            Transformer::StandardScaler(scaler) => scaler.observe(input),
+            Transformer::MinMaxScaler(scaler) => scaler.observe(input),
+            Transformer::FixedRangeScaler(_) => {}
+            Transformer::OneHotEncoder(encoder) => return encoder.observe(input),
+        }
+
+        Ok(())
+    }
+
+    pub fn transform(&self, input: &[f64]) -> Vec<f64> {
+        match self {
+            Transformer::StandardScaler(scaler) => scaler.transform(input),
+            Transformer::MinMaxScaler(scaler) => scaler.transform(input),
+            Transformer::FixedRangeScaler(scaler) => scaler.transform(input),
+            Transformer::OneHotEncoder(encoder) => encoder.transform(input),
+        }
+    }
+
+    // Number of features the transformer was fitted on, None when it takes any
+    fn features(&self) -> Option<usize> {
+        match self {
+            Transformer::StandardScaler(scaler) => Some(scaler.features()),
+            Transformer::MinMaxScaler(scaler) => Some(scaler.features()),
+            Transformer::FixedRangeScaler(_) | Transformer::OneHotEncoder(_) => None,
+        }
+    }
+
+    // Fails when the input doesn't have the features the transformer was fitted on,
+    // or the columns an encoder reads
+    fn check_features(&self, input: &[f64]) -> Result<(), NeuraError> {
+        if let Transformer::OneHotEncoder(encoder) = self {
+            return encoder.check_width(input);
+        }
+
+        match self.features() {
+            Some(features) if features != input.len() => Err(NeuraError::shape_mismatch(
+                "pipeline input",
+                (features, 1),
+                (input.len(), 1),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // Same transformer before fitting
+    fn reset(&self) -> Self {
+        match self {
+            Transformer::StandardScaler(_) => Transformer::StandardScaler(StandardScaler::new()),
+            Transformer::MinMaxScaler(_) => Transformer::MinMaxScaler(MinMaxScaler::new()),
+            Transformer::FixedRangeScaler(scaler) => Transformer::FixedRangeScaler(*scaler),
+            Transformer::OneHotEncoder(encoder) => Transformer::OneHotEncoder(encoder.reset()),
+        }
+    }
+}
+
+// Input transformers applied in order, plus the encoder of the target labels.
+// A model holding a pipeline applies it to every input it gets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pipeline {
+    steps: Vec<Transformer>,
+    labels: Option<LabelEncoder>,
+}
+
+impl Pipeline {
+    pub fn new(steps: Vec<Transformer>) -> Self {
+        Self {
+            steps,
+            labels: None,
+        }
+    }
+
+    pub fn with_labels(steps: Vec<Transformer>, labels: LabelEncoder) -> Self {
+        Self {
+            steps,
+            labels: Some(labels),
+        }
+    }
+
+    // Every step is fitted on the inputs transformed by the previous ones, so the
+    // dataset is read once per step. All the inputs must have as many features.
+    pub fn fit<T: Float>(&mut self, dataset: &dyn Dataset<T>) -> Result<(), NeuraError> {
+        for step in 0..self.steps.len() {
+            self.steps[step] = self.steps[step].reset();
+
+            let mut observed = false;
+
+            for batch in dataset.batches(256, false) {
+                let batch = batch?;
+
+                for input in batch.inputs.iter() {
+                    let input = self.steps[..step]
+                        .iter()
+                        .fold(to_vec(input), |input, previous| previous.transform(&input));
+
+                    if observed {
+                        self.steps[step].check_features(&input)?;
+                    }
+
+                    self.steps[step].observe(&input)?;
+
+                    observed = true;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn transform<T: Float>(&self, input: &DMatrix<T>) -> Result<DMatrix<T>, NeuraError> {
+        let mut output = to_vec(input);
+
+        for step in self.steps.iter() {
+            step.check_features(&output)?;
+
+            output = step.transform(&output);
+        }
+
+        Ok(DMatrix::from_iterator(
+            output.len(),
+            1,
+            output.into_iter().map(T::cast),
+        ))
+    }
+
+    pub fn get_steps_reference(&self) -> &Vec<Transformer> {
+        &self.steps
+    }
+
+    pub fn get_labels_reference(&self) -> Option<&LabelEncoder> {
+        self.labels.as_ref()
+    }
+}
+
+fn to_vec<T: Float>(input: &DMatrix<T>) -> Vec<f64> {
+    input.iter().map(|value| value.as_f64()).collect()
+}
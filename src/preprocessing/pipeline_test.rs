@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{
+        data::dataset::InMemoryDataset,
+        error::NeuraError,
+        preprocessing::{
+            encoders::{LabelEncoder, OneHotEncoder},
+            pipeline::{Pipeline, Transformer},
+            scalers::{FixedRangeScaler, MinMaxScaler, StandardScaler},
+        },
+    };
+
+    fn dataset() -> InMemoryDataset {
+        let x = vec![
+            DMatrix::from_vec(3, 1, vec![1.0, 2.0, 10.0]),
+            DMatrix::from_vec(3, 1, vec![3.0, 1.0, 10.0]),
+            DMatrix::from_vec(3, 1, vec![5.0, 2.0, 10.0]),
+        ];
+        let y = vec![DMatrix::zeros(1, 1); 3];
+
+        InMemoryDataset::new(x, y).unwrap()
+    }
+
+    #[test]
+    fn test_scalers() {
+        let mut standard = StandardScaler::new();
+        let mut min_max = MinMaxScaler::new();
+
+        for input in [[1.0, 4.0], [3.0, 4.0], [5.0, 4.0]] {
+            standard.observe(&input);
+            min_max.observe(&input);
+        }
+
+        assert_eq!(&vec![3.0, 4.0], standard.get_means_reference());
+        assert!((standard.get_stds_clone()[0] - (8.0_f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(vec![0.0, 0.0], standard.transform(&[3.0, 4.0]));
+        assert_eq!(vec![0.5, 0.0], min_max.transform(&[3.0, 4.0]));
+
+        let fixed = FixedRangeScaler::new(0.0, 255.0);
+
+        assert_eq!(vec![0.0, 0.2, 1.0], fixed.transform(&[0.0, 51.0, 255.0]));
+    }
+
+    #[test]
+    fn test_fixed_range_scaler_is_not_fitted() {
+        let mut pipeline = Pipeline::new(vec![Transformer::FixedRangeScaler(
+            FixedRangeScaler::new(0.0, 20.0),
+        )]);
+
+        pipeline.fit(&dataset()).unwrap();
+
+        assert_eq!(
+            DMatrix::from_vec(3, 1, vec![0.05, 0.1, 0.5]),
+            pipeline
+                .transform(&DMatrix::from_vec(3, 1, vec![1.0, 2.0, 10.0]))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encoders() {
+        let mut one_hot = OneHotEncoder::new(vec![1]);
+
+        for input in [[0.5, 2.0], [0.1, 1.0], [0.3, 2.0]] {
+            one_hot.observe(&input).unwrap();
+        }
+
+        assert_eq!(&vec![vec![1.0, 2.0]], one_hot.get_categories_reference());
+        assert_eq!(vec![0.5, 0.0, 1.0], one_hot.transform(&[0.5, 2.0]));
+        assert_eq!(vec![0.5, 0.0, 0.0], one_hot.transform(&[0.5, 7.0]));
+
+        let mut labels = LabelEncoder::new();
+        labels.fit(["dog", "cat", "dog"]);
+
+        assert_eq!(Some(1), labels.transform("dog"));
+        assert_eq!(Some("cat"), labels.inverse_transform(0));
+        assert_eq!(None, labels.transform("bird"));
+        assert_eq!(
+            Some(DMatrix::from_vec(2, 1, vec![0.0_f32, 1.0])),
+            labels.one_hot("dog")
+        );
+    }
+
+    #[test]
+    fn test_pipeline_fits_each_step_on_the_previous_output() {
+        let mut pipeline = Pipeline::new(vec![
+            Transformer::OneHotEncoder(OneHotEncoder::new(vec![1])),
+            Transformer::MinMaxScaler(MinMaxScaler::new()),
+        ]);
+
+        pipeline.fit(&dataset()).unwrap();
+
+        let output = pipeline
+            .transform(&DMatrix::from_vec(3, 1, vec![3.0_f32, 1.0, 10.0]))
+            .unwrap();
+
+        assert_eq!(DMatrix::from_vec(4, 1, vec![0.5, 1.0, 0.0, 0.0]), output);
+
+        // Fitting again starts from scratch
+        pipeline.fit(&dataset()).unwrap();
+
+        assert_eq!(
+            output,
+            pipeline
+                .transform(&DMatrix::from_vec(3, 1, vec![3.0_f32, 1.0, 10.0]))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_transform_rejects_other_feature_counts() {
+        let mut pipeline = Pipeline::new(vec![Transformer::StandardScaler(StandardScaler::new())]);
+        pipeline.fit(&dataset()).unwrap();
+
+        assert!(matches!(
+            pipeline.transform(&DMatrix::from_vec(2, 1, vec![3.0_f32, 1.0])),
+            Err(NeuraError::ShapeMismatch {
+                expected: (3, 1),
+                found: (2, 1),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_one_hot_encoder_rejects_short_rows() {
+        let mut one_hot = OneHotEncoder::new(vec![0, 3]);
+
+        assert!(matches!(
+            one_hot.observe(&[1.0, 2.0]),
+            Err(NeuraError::ShapeMismatch {
+                expected: (4, 1),
+                found: (2, 1),
+                ..
+            })
+        ));
+
+        let mut pipeline =
+            Pipeline::new(vec![Transformer::OneHotEncoder(OneHotEncoder::new(vec![
+                5,
+            ]))]);
+
+        assert!(pipeline.fit(&dataset()).is_err());
+        assert!(pipeline
+            .transform(&DMatrix::from_vec(3, 1, vec![3.0_f32, 1.0, 10.0]))
+            .is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+// Scales every feature to zero mean and unit variance. The statistics are
+// updated one sample at a time with Welford's algorithm.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StandardScaler {
+    count: usize,
+    means: Vec<f64>,
+    squared_deviations: Vec<f64>,
+}
+
+impl StandardScaler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&mut self, input: &[f64]) {
+        if self.count == 0 {
+            self.means = vec![0.0; input.len()];
+            self.squared_deviations = vec![0.0; input.len()];
+        }
+
+        self.count += 1;
+
+        for (i, &value) in input.iter().enumerate() {
+            let deviation = value - self.means[i];
+
+            self.means[i] += deviation / self.count as f64;
+            self.squared_deviations[i] += deviation * (value - self.means[i]);
+        }
+    }
+
+    // Constant features are only centered
+    pub fn transform(&self, input: &[f64]) -> Vec<f64> {
+        input
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                let std = (self.squared_deviations[i] / self.count as f64).sqrt();
+
+                if std > 0.0 {
+                    (value - self.means[i]) / std
+                } else {
+                    value - self.means[i]
+                }
+            })
+            .collect()
+    }
+
+    // Number of features seen while fitting
+    pub fn features(&self) -> usize {
+        self.means.len()
+    }
+
+    pub fn get_means_reference(&self) -> &Vec<f64> {
+        &self.means
+    }
+
+    pub fn get_stds_clone(&self) -> Vec<f64> {
+        self.squared_deviations
+            .iter()
+            .map(|squared_deviation| (squared_deviation / self.count as f64).sqrt())
+            .collect()
+    }
+}
+
+// Maps every feature from the [min, max] seen while fitting to [0, 1]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MinMaxScaler {
+    maxs: Vec<f64>,
+    mins: Vec<f64>,
+}
+
+impl MinMaxScaler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn features(&self) -> usize {
+        self.mins.len()
+    }
+
+    pub fn observe(&mut self, input: &[f64]) {
+        if self.mins.is_empty() {
+            self.mins = input.to_vec();
+            self.maxs = input.to_vec();
+        }
+
+        for (i, &value) in input.iter().enumerate() {
+            self.mins[i] = self.mins[i].min(value);
+            self.maxs[i] = self.maxs[i].max(value);
+        }
+    }
+
+    // Constant features become 0
+    pub fn transform(&self, input: &[f64]) -> Vec<f64> {
+        input
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                let range = self.maxs[i] - self.mins[i];
+
+                if range > 0.0 {
+                    (value - self.mins[i]) / range
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+}
+
+// Maps every feature from a range known beforehand, like [0, 255] for pixels,
+// to [0, 1]. Fitting leaves it unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FixedRangeScaler {
+    min: f64,
+    max: f64,
+}
+
+impl FixedRangeScaler {
+    pub fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    pub fn transform(&self, input: &[f64]) -> Vec<f64> {
+        let range = self.max - self.min;
+
+        input
+            .iter()
+            .map(|&value| {
+                if range > 0.0 {
+                    (value - self.min) / range
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    pub fn get_range(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+}